@@ -0,0 +1,145 @@
+use crate::implementations::{
+    tuples::*,
+    matrices::*,
+    ray::*
+};
+
+// slack added around boxes when testing rays against them, so shapes
+// hit right at their edge aren't culled by round-off
+static BOX_EPSILON: f32 = 0.001;
+
+// axis-aligned bounding box, infinite extents are allowed for
+// unbounded shapes like planes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Vec4,
+    pub max: Vec4
+}
+
+impl BoundingBox {
+    pub fn new(min: Vec4, max: Vec4) -> BoundingBox {
+        BoundingBox {
+            min,
+            max
+        }
+    }
+
+    // box containing nothing, grows as points are added
+    pub fn empty() -> BoundingBox {
+        BoundingBox {
+            min: point(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: point(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)
+        }
+    }
+
+    pub fn infinite() -> BoundingBox {
+        BoundingBox {
+            min: point(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: point(f32::INFINITY, f32::INFINITY, f32::INFINITY)
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.min.0.is_finite() && self.min.1.is_finite() && self.min.2.is_finite()
+            && self.max.0.is_finite() && self.max.1.is_finite() && self.max.2.is_finite()
+    }
+
+    // true for boxes that haven't had any points added
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1 || self.min.2 > self.max.2
+    }
+
+    pub fn add_point(&mut self, p: &Vec4) {
+        self.min = point(self.min.0.min(p.0), self.min.1.min(p.1), self.min.2.min(p.2));
+        self.max = point(self.max.0.max(p.0), self.max.1.max(p.1), self.max.2.max(p.2));
+    }
+
+    pub fn merge(&mut self, other: &BoundingBox) {
+        // the corners of an empty box are infinite, adding them would
+        // stretch this box out to infinity
+        if other.is_empty() {
+            return
+        }
+        self.add_point(&other.min);
+        self.add_point(&other.max);
+    }
+
+    pub fn contains_point(&self, p: &Vec4) -> bool {
+        self.min.0 <= p.0 && p.0 <= self.max.0
+            && self.min.1 <= p.1 && p.1 <= self.max.1
+            && self.min.2 <= p.2 && p.2 <= self.max.2
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(&other.min) && self.contains_point(&other.max)
+    }
+
+    pub fn centroid(&self) -> Vec4 {
+        point(
+            (self.min.0 + self.max.0) / 2.0,
+            (self.min.1 + self.max.1) / 2.0,
+            (self.min.2 + self.max.2) / 2.0
+        )
+    }
+
+    // box around all eight transformed corners, empty boxes stay empty
+    // and boxes with infinite extents can't be transformed exactly so
+    // they become infinite
+    pub fn transform(&self, m: &Matrix4x4) -> BoundingBox {
+        if self.is_empty() {
+            return BoundingBox::empty()
+        }
+        if !self.is_finite() {
+            return BoundingBox::infinite()
+        }
+
+        let corners = [
+            self.min,
+            point(self.min.0, self.min.1, self.max.2),
+            point(self.min.0, self.max.1, self.min.2),
+            point(self.min.0, self.max.1, self.max.2),
+            point(self.max.0, self.min.1, self.min.2),
+            point(self.max.0, self.min.1, self.max.2),
+            point(self.max.0, self.max.1, self.min.2),
+            self.max
+        ];
+        let mut result = BoundingBox::empty();
+        for corner in corners.iter() {
+            result.add_point(&(m * corner));
+        }
+        result
+    }
+
+    // whether the ray's line passes through the box, hits behind the
+    // origin count too since refraction tracking needs them
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let (xtmin, xtmax) = check_axis(ray.origin.0, ray.direction.0, self.min.0, self.max.0);
+        let (ytmin, ytmax) = check_axis(ray.origin.1, ray.direction.1, self.min.1, self.max.1);
+        let (ztmin, ztmax) = check_axis(ray.origin.2, ray.direction.2, self.min.2, self.max.2);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+        tmin <= tmax
+    }
+}
+
+// (near, far) distances to the box's slab on one axis
+fn check_axis(origin: f32, direction: f32, min: f32, max: f32) -> (f32, f32) {
+    let min = min - BOX_EPSILON;
+    let max = max + BOX_EPSILON;
+    if direction.abs() < EPSILON {
+        // parallel rays are either always or never inside the slab
+        if min <= origin && origin <= max {
+            return (f32::NEG_INFINITY, f32::INFINITY)
+        }
+        return (f32::INFINITY, f32::NEG_INFINITY)
+    }
+
+    let tmin = (min - origin) / direction;
+    let tmax = (max - origin) / direction;
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
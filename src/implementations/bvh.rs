@@ -0,0 +1,127 @@
+use crate::implementations::{
+    bounds::*,
+    ray::*,
+    shape::*
+};
+
+// leaves stop splitting once they hold this many shapes
+static LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BvhNode {
+    Leaf {
+        bounds: BoundingBox,
+        indices: Vec<usize>
+    },
+    Branch {
+        bounds: BoundingBox,
+        left: Box<BvhNode>,
+        right: Box<BvhNode>
+    }
+}
+
+// bounding volume hierarchy over a slice of shapes, storing indices
+// into it so the shapes themselves stay where they are
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bvh {
+    root: Option<BvhNode>,
+    // shapes with infinite bounds (planes etc.) can't be culled
+    unbounded: Vec<usize>,
+    shape_count: usize
+}
+
+impl BvhNode {
+    fn build(mut entries: Vec<(usize, BoundingBox)>) -> BvhNode {
+        let mut bounds = BoundingBox::empty();
+        for (_, entry_bounds) in entries.iter() {
+            bounds.merge(entry_bounds);
+        }
+
+        if entries.len() <= LEAF_SIZE {
+            return BvhNode::Leaf {
+                bounds,
+                indices: entries.iter().map(|(index, _)| *index).collect()
+            }
+        }
+
+        // split at the median centroid along the axis they spread out most on
+        let mut centroid_bounds = BoundingBox::empty();
+        for (_, entry_bounds) in entries.iter() {
+            centroid_bounds.add_point(&entry_bounds.centroid());
+        }
+        let extent = &centroid_bounds.max - &centroid_bounds.min;
+        let axis_value: fn(&BoundingBox) -> f32 = if extent.0 >= extent.1 && extent.0 >= extent.2 {
+            |b| b.centroid().0
+        } else if extent.1 >= extent.2 {
+            |b| b.centroid().1
+        } else {
+            |b| b.centroid().2
+        };
+        entries.sort_by(|(_, a), (_, b)| axis_value(a).partial_cmp(&axis_value(b)).unwrap());
+        let right = entries.split_off(entries.len() / 2);
+
+        BvhNode::Branch {
+            bounds,
+            left: Box::new(BvhNode::build(entries)),
+            right: Box::new(BvhNode::build(right))
+        }
+    }
+
+    fn candidates(&self, ray: &Ray, result: &mut Vec<usize>) {
+        match self {
+            BvhNode::Leaf { bounds, indices } => {
+                if bounds.intersects(ray) {
+                    result.extend(indices.iter());
+                }
+            },
+            BvhNode::Branch { bounds, left, right } => {
+                if bounds.intersects(ray) {
+                    left.candidates(ray, result);
+                    right.candidates(ray, result);
+                }
+            }
+        }
+    }
+}
+
+impl Bvh {
+    // shapes are bounded in the space they sit in, so this works for both
+    // a world's shapes and a group's children
+    pub fn build(shapes: &[Box<dyn Shape>]) -> Bvh {
+        let mut bounded: Vec<(usize, BoundingBox)> = Vec::new();
+        let mut unbounded: Vec<usize> = Vec::new();
+        for (index, shape) in shapes.iter().enumerate() {
+            let bounds = shape.parent_space_bounds();
+            if bounds.is_finite() {
+                bounded.push((index, bounds));
+            } else {
+                unbounded.push(index);
+            }
+        }
+
+        Bvh {
+            root: if bounded.is_empty() {
+                None
+            } else {
+                Some(BvhNode::build(bounded))
+            },
+            unbounded,
+            shape_count: shapes.len()
+        }
+    }
+
+    pub fn shape_count(&self) -> usize {
+        self.shape_count
+    }
+
+    // indices of the shapes the ray might hit, in ascending order so
+    // intersections come out in the same order as testing every shape
+    pub fn candidates(&self, ray: &Ray) -> Vec<usize> {
+        let mut result = self.unbounded.clone();
+        if let Some(root) = &self.root {
+            root.candidates(ray, &mut result);
+        }
+        result.sort_unstable();
+        result
+    }
+}
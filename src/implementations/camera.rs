@@ -1,19 +1,45 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread
+};
+
 use crate::implementations::{
     canvas::Canvas, 
     matrices::*, 
+    random::Rng,
     ray::*, 
     tuples::*, 
     world::World
 };
 
+// how many rays are averaged into each pixel
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Sampling {
+    // n x n evenly spaced samples, Grid(1) is a single ray through the center
+    Grid(usize),
+    // one randomly placed sample inside each cell of an n x n grid
+    Jittered(usize),
+    // 2 x 2 grid, replaced by an n x n grid only when the colors of the
+    // first samples differ by more than threshold in any channel
+    Adaptive {
+        grid: usize,
+        threshold: f32
+    }
+}
+
 pub struct Camera {
     pub hsize: usize,
     pub vsize: usize,
     pub field_of_view: f32,
-    pub transform: Matrix4x4,
+    // set with set_transform so the inverse stays in sync
+    transform: Matrix4x4,
+    inverse: Matrix4x4,
     pub half_height: f32,
     pub half_width: f32,
-    pub pixel_size: f32
+    pub pixel_size: f32,
+    // number of threads render splits rows between
+    pub threads: usize,
+    pub sampling: Sampling
 }
 
 impl Camera {
@@ -34,21 +60,39 @@ impl Camera {
             vsize,
             field_of_view,
             transform: Matrix4x4::ident(),
+            inverse: Matrix4x4::ident(),
             half_height,
             half_width,
-            pixel_size: (half_width * 2.0) / (hsize as f32)
+            pixel_size: (half_width * 2.0) / (hsize as f32),
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            sampling: Sampling::Grid(1)
         }
     }
 
+    pub fn transform(&self) -> &Matrix4x4 {
+        &self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Matrix4x4) {
+        self.transform = transform;
+        self.inverse = transform.invert();
+    }
+
     pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
-        let xoffset = ((px as f32) + 0.5) * self.pixel_size;
-        let yoffset = ((py as f32) + 0.5) * self.pixel_size;
+        self.ray_for_pixel_offset(px, py, 0.5, 0.5)
+    }
+
+    // ray through a point inside the pixel, offsets run from 0 to 1
+    // across the pixel
+    pub fn ray_for_pixel_offset(&self, px: usize, py: usize, px_offset: f32, py_offset: f32) -> Ray {
+        let xoffset = ((px as f32) + px_offset) * self.pixel_size;
+        let yoffset = ((py as f32) + py_offset) * self.pixel_size;
 
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
 
-        let pixel = &self.transform.invert() * &point(world_x, world_y, -1.0);
-        let origin = &self.transform.invert() * &point(0.0, 0.0, 0.0);
+        let pixel = &self.inverse * &point(world_x, world_y, -1.0);
+        let origin = &self.inverse * &point(0.0, 0.0, 0.0);
         let direction = (&pixel - &origin).normalize();
         
         Ray {
@@ -57,47 +101,147 @@ impl Camera {
         }
     }
 
+    // color of the rays through an n x n grid of cells in the pixel,
+    // jittered within their cells if rng is given
+    fn sample_grid(&self, world: &World, reflection_limit: u32, px: usize, py: usize, n: usize, mut rng: Option<&mut Rng>) -> Vec<Vec4> {
+        let mut samples: Vec<Vec4> = Vec::with_capacity(n * n);
+        for sy in 0..n {
+            for sx in 0..n {
+                let (jx, jy) = match rng.as_mut() {
+                    Some(rng) => (rng.next_f32(), rng.next_f32()),
+                    None => (0.5, 0.5)
+                };
+                let ray = self.ray_for_pixel_offset(
+                    px,
+                    py,
+                    (sx as f32 + jx) / n as f32,
+                    (sy as f32 + jy) / n as f32
+                );
+                samples.push(world.color_at(&ray, reflection_limit));
+            }
+        }
+        samples
+    }
+
+    // averaged color of a pixel and how many rays it took
+    pub fn color_for_pixel(&self, world: &World, reflection_limit: u32, px: usize, py: usize) -> (Vec4, usize) {
+        // rays that were traced but aren't part of the average
+        let mut discarded = 0;
+        let samples = match self.sampling {
+            Sampling::Grid(n) => self.sample_grid(world, reflection_limit, px, py, n.max(1), None),
+            Sampling::Jittered(n) => {
+                let mut rng = Rng::new((py * self.hsize + px) as u64);
+                self.sample_grid(world, reflection_limit, px, py, n.max(1), Some(&mut rng))
+            },
+            Sampling::Adaptive { grid, threshold } => {
+                let probes = self.sample_grid(world, reflection_limit, px, py, 2, None);
+                let mut low = probes[0];
+                let mut high = probes[0];
+                for sample in probes.iter() {
+                    low = color(low.0.min(sample.0), low.1.min(sample.1), low.2.min(sample.2));
+                    high = color(high.0.max(sample.0), high.1.max(sample.1), high.2.max(sample.2));
+                }
+                let contrast = (high.0 - low.0).max(high.1 - low.1).max(high.2 - low.2);
+                if contrast > threshold {
+                    // the probes sit at different positions than most of the
+                    // grid's, averaging them in would weight those parts of the
+                    // pixel twice, so a refined pixel is exactly Grid(grid)
+                    discarded = probes.len();
+                    self.sample_grid(world, reflection_limit, px, py, grid.max(1), None)
+                } else {
+                    probes
+                }
+            }
+        };
+
+        let mut sum = color(0.0, 0.0, 0.0);
+        for sample in samples.iter() {
+            sum = &sum + sample;
+        }
+        (&sum * (1.0 / samples.len() as f32), samples.len() + discarded)
+    }
+
     pub fn render(&self, world: &World, reflection_limit: u32) -> Canvas {
+        let (canvas, sample_count) = self.render_with_sample_count(world, reflection_limit);
+        println!("\u{001b}[37;1mRendered with \u{001b}[33;1m{}\u{001b}[37;1m samples", sample_count);
+        canvas
+    }
+
+    // render along with the total number of rays fired through pixels
+    pub fn render_with_sample_count(&self, world: &World, reflection_limit: u32) -> (Canvas, usize) {
         let mut canvas = Canvas::new(self.hsize, self.vsize);
+        let sample_count = AtomicUsize::new(0);
 
-        let mut last_percentage_done = 1;
-        for y in 0..self.vsize - 1 {
-            for x in 0..self.hsize - 1 {
-                let percentage_done = ((x + (y * self.hsize)) * 10) / ((self.hsize - 1) * (self.vsize - 1));
-                
-                if last_percentage_done != percentage_done {
-                    let mut arrow_string = String::new();
-                    for _ in 0..percentage_done {
-                        arrow_string.push('=');
-                    }
-                    if percentage_done != 10 {
-                        arrow_string.push('>');
-                        for _ in 0..9 - percentage_done {
-                            arrow_string.push(' ');
+        // rows are handed out one at a time to whichever thread is free
+        let next_row = AtomicUsize::new(0);
+        let rows_done = AtomicUsize::new(0);
+        let last_percentage_done = AtomicUsize::new(0);
+        print_progress(0);
+        let rendered_rows: Vec<(usize, Vec<Vec4>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.max(1))
+                .map(|_| scope.spawn(|| {
+                    let mut rows: Vec<(usize, Vec<Vec4>)> = Vec::new();
+                    loop {
+                        let y = next_row.fetch_add(1, Ordering::SeqCst);
+                        if y >= self.vsize - 1 {
+                            break;
+                        }
+
+                        let mut row: Vec<Vec4> = Vec::with_capacity(self.hsize - 1);
+                        for x in 0..self.hsize - 1 {
+                            let (color, samples) = self.color_for_pixel(world, reflection_limit, x, y);
+                            row.push(color);
+                            sample_count.fetch_add(samples, Ordering::SeqCst);
+                        }
+                        rows.push((y, row));
+
+                        let done = rows_done.fetch_add(1, Ordering::SeqCst) + 1;
+                        let percentage_done = (done * 10) / (self.vsize - 1);
+                        // only the thread that moves the percentage forward reports it
+                        if last_percentage_done.fetch_max(percentage_done, Ordering::SeqCst) < percentage_done {
+                            print_progress(percentage_done);
                         }
                     }
-                    println!(
-                        "\u{001b}[37;1mRender is  \u{001b}[33;1m{}0%\u{001b}[37;1m {}complete \u{001b}[32;1m[{}]", 
-                        if percentage_done == 0 {
-                            String::from(" ")
-                        } else {
-                            percentage_done.to_string()
-                        },
-                        if percentage_done != 10 {
-                            " "
-                        } else {
-                            ""
-                        },
-                        arrow_string
-                    );
-                }
-                last_percentage_done = percentage_done;
+                    rows
+                }))
+                .collect();
+            workers.into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
 
-                let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray, reflection_limit);
+        for (y, row) in rendered_rows {
+            for (x, color) in row.into_iter().enumerate() {
                 canvas.set(x, y, color);
             }
         }
-        canvas
+        (canvas, sample_count.into_inner())
+    }
+}
+
+fn print_progress(percentage_done: usize) {
+    let mut arrow_string = String::new();
+    for _ in 0..percentage_done {
+        arrow_string.push('=');
     }
+    if percentage_done != 10 {
+        arrow_string.push('>');
+        for _ in 0..9 - percentage_done {
+            arrow_string.push(' ');
+        }
+    }
+    println!(
+        "\u{001b}[37;1mRender is  \u{001b}[33;1m{}0%\u{001b}[37;1m {}complete \u{001b}[32;1m[{}]", 
+        if percentage_done == 0 {
+            String::from(" ")
+        } else {
+            percentage_done.to_string()
+        },
+        if percentage_done != 10 {
+            " "
+        } else {
+            ""
+        },
+        arrow_string
+    );
 }
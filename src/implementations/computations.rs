@@ -5,7 +5,7 @@ use crate::implementations::{
 
 pub struct Computations<'a> {
     pub t: f32,
-    pub object: &'a Shape,
+    pub object: &'a dyn Shape,
     pub point: Vec4,
     pub eyev: Vec4,
     pub normalv: Vec4,
@@ -1,62 +1,79 @@
+use std::cmp::Ordering;
+
 use crate::implementations::{
     computations::Computations, 
     shape::*,
-    ray::*
+    ray::*,
+    tuples::*
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Intersection<'a> {
-    pub object: &'a Shape,
-    pub t: f32
+    pub object: &'a dyn Shape,
+    pub t: f32,
+    // barycentric coordinates of the hit, only meaningful for triangles
+    pub u: f32,
+    pub v: f32,
+    // shape whose volume this surface bounds, for refraction tracking;
+    // the object itself unless it was hit as part of a CSG shape
+    pub container: &'a dyn Shape
 }
 
 static BUMP_EPSILON: f32 = 0.01;
 impl<'a> Intersection<'a> {
+    pub fn new(t: f32, object: &'a dyn Shape) -> Intersection<'a> {
+        Intersection {
+            object,
+            t,
+            u: 0.0,
+            v: 0.0,
+            container: object
+        }
+    }
+
+    pub fn new_with_uv(t: f32, object: &'a dyn Shape, u: f32, v: f32) -> Intersection<'a> {
+        Intersection {
+            object,
+            t,
+            u,
+            v,
+            container: object
+        }
+    }
+
     pub fn prepare_computations(&self, ray: &Ray, intersections: Vec<Intersection>) -> Computations {
-        let hit = hit(intersections.clone());
-        let mut containers: Vec<Shape> = Vec::new();
+        // intersections whose containing volume the ray is currently inside,
+        // the refractive index comes from the surface the ray entered through
+        let mut containers: Vec<&Intersection> = Vec::new();
         let mut n1: Option<f32> = None;
         let mut n2: Option<f32> = None;
         for i in intersections.iter() {
-            if let Some(hit) = hit.clone() {
-                if hit == *i {
-                    if containers.is_empty() {
-                        n1 = Some(1.0);
-                    } else {
-                        n1 = Some(containers.last().unwrap().material.refractive_index);
-                    }
+            if *self == *i {
+                if containers.is_empty() {
+                    n1 = Some(1.0);
+                } else {
+                    n1 = Some(containers.last().unwrap().object.material().refractive_index);
                 }
             }
 
-            //println!("Object: {:?} Containers: {:?}", i.object, containers);
-            if containers.contains(i.object) { 
-                //println!("Removing object");
-                for (index, object) in containers.iter().enumerate() {
-                    if object == i.object {
-                        containers.remove(index);
-                        //println!("Removed object");
-                        break;
-                    }
-                }
+            if let Some(index) = containers.iter().position(|c| c.container.id() == i.container.id()) {
+                containers.remove(index);
             } else {
-                //println!("Adding object");
-                containers.push(i.object.clone());
+                containers.push(i);
             }
 
-            if let Some(hit) = hit {
-                if hit == *i {
-                    if containers.is_empty() {
-                        n2 = Some(1.0);
-                    } else {
-                        n2 = Some(containers.last().unwrap().material.refractive_index);
-                    }
-                    break;
+            if *self == *i {
+                if containers.is_empty() {
+                    n2 = Some(1.0);
+                } else {
+                    n2 = Some(containers.last().unwrap().object.material().refractive_index);
                 }
+                break;
             }
         }
 
         let ray_position = ray.position(self.t);
-        let normal = self.object.normal_at(&ray_position);
+        let normal = self.object.normal_at(&ray_position, self);
         let normal_epsilon = &normal * BUMP_EPSILON;
         let over_point = &ray_position + &normal_epsilon;
         let under_point = &ray_position - &normal_epsilon;
@@ -101,3 +118,16 @@ pub fn hit(intersections: Vec<Intersection>) -> Option<Intersection> {
             }
         })
 }
+
+// order intersections front to back along the ray
+pub fn sort_intersections(intersections: &mut [Intersection]) {
+    intersections.sort_by(|a, b| 
+        if fequals(a.t, b.t) {
+            Ordering::Equal
+        } else if a.t > b.t {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    );
+}
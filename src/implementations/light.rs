@@ -1,16 +1,229 @@
 use crate::implementations::{
     tuples::*,
     material::*,
+    matrices::degrees_to_radians,
+    random::Rng,
     shape::*
 };
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LightType {
+    Point,
+    // rectangle from corner spanned by usteps cells of uvec and vsteps
+    // cells of vvec, sampled once per cell
+    Area {
+        corner: Vec4,
+        uvec: Vec4,
+        usteps: usize,
+        vvec: Vec4,
+        vsteps: usize,
+        // randomly place each sample inside its cell instead of at its center
+        jitter: bool
+    },
+    // cone of light from position along direction, full strength within
+    // inner_angle of the axis and fading out to nothing at outer_angle,
+    // both in degrees
+    Spot {
+        direction: Vec4,
+        inner_angle: f32,
+        outer_angle: f32
+    },
+    // infinitely far away light shining along direction, like the sun
+    Directional {
+        direction: Vec4
+    }
+}
+
+// how positional lights fade with distance d from the light
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Attenuation {
+    None,
+    // 1 / (constant + linear * d + quadratic * d^2), build with
+    // Attenuation::polynomial so the denominator can't reach zero
+    Polynomial {
+        constant: f32,
+        linear: f32,
+        quadratic: f32
+    },
+    // 1 / d^2, at full strength within one unit of the light rather
+    // than growing without bound
+    InverseSquare
+}
+
+impl Attenuation {
+    pub fn polynomial(constant: f32, linear: f32, quadratic: f32) -> Attenuation {
+        if constant <= 0.0 || linear < 0.0 || quadratic < 0.0 {
+            panic!("Polynomial attenuation needs a positive constant and non-negative linear and quadratic terms");
+        }
+        Attenuation::Polynomial {
+            constant,
+            linear,
+            quadratic
+        }
+    }
+
+    pub fn factor(&self, distance: f32) -> f32 {
+        match *self {
+            Attenuation::None => 1.0,
+            Attenuation::Polynomial { constant, linear, quadratic } => {
+                1.0 / (constant + linear * distance + quadratic * distance * distance)
+            },
+            Attenuation::InverseSquare => {
+                let distance = distance.max(1.0);
+                1.0 / (distance * distance)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Light {
     pub intensity: Vec4,
-    pub position: Vec4
+    // center of the light for area lights, unused by directional lights
+    pub position: Vec4,
+    pub light_type: LightType,
+    pub attenuation: Attenuation
 }
 
-pub fn lighting(material: &Material, shape: &Shape, light: &Light, point: &Vec4, eyev: &Vec4, normalv: &Vec4, is_shadowed: bool) -> Vec4 {
+impl Light {
+    pub fn new(position: Vec4, intensity: Vec4) -> Light {
+        Light {
+            intensity,
+            position,
+            light_type: LightType::Point,
+            attenuation: Attenuation::None
+        }
+    }
+
+    // angles are in degrees from direction, the light fades out between
+    // the inner and outer cones
+    pub fn spot(position: Vec4, direction: Vec4, inner_angle: f32, outer_angle: f32, intensity: Vec4) -> Light {
+        if inner_angle > outer_angle {
+            panic!("Spotlight inner angle {} is wider than its outer angle {}", inner_angle, outer_angle);
+        }
+        Light {
+            intensity,
+            position,
+            light_type: LightType::Spot {
+                direction: direction.normalize(),
+                inner_angle,
+                outer_angle
+            },
+            attenuation: Attenuation::None
+        }
+    }
+
+    pub fn directional(direction: Vec4, intensity: Vec4) -> Light {
+        Light {
+            intensity,
+            position: point(0.0, 0.0, 0.0),
+            light_type: LightType::Directional {
+                direction: direction.normalize()
+            },
+            attenuation: Attenuation::None
+        }
+    }
+
+    // full_uvec and full_vvec are the edges of the whole rectangle
+    pub fn area(corner: Vec4, full_uvec: Vec4, usteps: usize, full_vvec: Vec4, vsteps: usize, jitter: bool, intensity: Vec4) -> Light {
+        Light {
+            intensity,
+            position: &(&corner + &(&full_uvec * 0.5)) + &(&full_vvec * 0.5),
+            light_type: LightType::Area {
+                corner,
+                uvec: &full_uvec * (1.0 / usteps as f32),
+                usteps,
+                vvec: &full_vvec * (1.0 / vsteps as f32),
+                vsteps,
+                jitter
+            },
+            attenuation: Attenuation::None
+        }
+    }
+
+    // point inside cell (u, v) of an area light, jittered by rng if given
+    pub fn point_on_light(&self, u: usize, v: usize, rng: Option<&mut Rng>) -> Vec4 {
+        match self.light_type {
+            LightType::Point | LightType::Spot { .. } | LightType::Directional { .. } => self.position,
+            LightType::Area { corner, uvec, vvec, .. } => {
+                let (ju, jv) = match rng {
+                    Some(rng) => (rng.next_f32(), rng.next_f32()),
+                    None => (0.5, 0.5)
+                };
+                &(&corner + &(&uvec * (u as f32 + ju))) + &(&vvec * (v as f32 + jv))
+            }
+        }
+    }
+
+    // positions the light is sampled at when lighting p, the jitter is
+    // seeded from p so a render comes out the same every time
+    pub fn samples(&self, p: &Vec4) -> Vec<Vec4> {
+        match self.light_type {
+            LightType::Point | LightType::Spot { .. } | LightType::Directional { .. } => vec![self.position],
+            LightType::Area { usteps, vsteps, jitter, .. } => {
+                let mut rng = Rng::new(
+                    (p.0.to_bits() as u64)
+                        ^ ((p.1.to_bits() as u64) << 21)
+                        ^ ((p.2.to_bits() as u64) << 42)
+                );
+                let mut samples: Vec<Vec4> = Vec::with_capacity(usteps * vsteps);
+                for v in 0..vsteps {
+                    for u in 0..usteps {
+                        samples.push(self.point_on_light(u, v, if jitter { Some(&mut rng) } else { None }));
+                    }
+                }
+                samples
+            }
+        }
+    }
+
+    // unit vector from p towards a sample on the light and how far away
+    // the sample is, directional lights are infinitely far in one direction
+    pub fn direction_from(&self, sample: &Vec4, p: &Vec4) -> (Vec4, f32) {
+        match self.light_type {
+            LightType::Directional { direction } => (-direction, f32::INFINITY),
+            _ => {
+                let diff = sample - p;
+                (diff.normalize(), diff.mag())
+            }
+        }
+    }
+
+    // how much of the light is left after travelling distance, directional
+    // lights are infinitely far away and don't fade
+    pub fn falloff(&self, distance: f32) -> f32 {
+        match self.light_type {
+            LightType::Directional { .. } => 1.0,
+            _ => self.attenuation.factor(distance)
+        }
+    }
+
+    // how much of the light falls on p because of its shape, 1 for
+    // everything but spotlights
+    pub fn spot_factor(&self, p: &Vec4) -> f32 {
+        match self.light_type {
+            LightType::Spot { direction, inner_angle, outer_angle } => {
+                let cos_angle = (p - &self.position).normalize().dot(&direction);
+                let cos_inner = degrees_to_radians(inner_angle).cos();
+                let cos_outer = degrees_to_radians(outer_angle).cos();
+                if cos_angle >= cos_inner {
+                    return 1.0
+                }
+                if cos_angle <= cos_outer {
+                    return 0.0
+                }
+                // smoothstep between the edges of the cone
+                let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+                t * t * (3.0 - 2.0 * t)
+            },
+            _ => 1.0
+        }
+    }
+}
+
+// light_intensity is how much of the light reaches the point in each
+// channel, black when fully shadowed
+pub fn lighting(material: &Material, shape: &dyn Shape, light: &Light, point: &Vec4, eyev: &Vec4, normalv: &Vec4, light_intensity: &Vec4) -> Vec4 {
     let material_color: Vec4;
     //println!("Material shape: {:?}, Material pattern: {:?}", shape.shape_type, material.pattern);
     if let Some(pattern) = &material.pattern {
@@ -19,31 +232,33 @@ pub fn lighting(material: &Material, shape: &Shape, light: &Light, point: &Vec4,
         material_color = material.color;
     }
     let effective_color = &material_color * &light.intensity;
-    let lightv = (&light.position - point).normalize();
     let ambient = &effective_color * material.ambient;
-    if is_shadowed {
+    let light_intensity = light_intensity * light.spot_factor(point);
+    if light_intensity.0 <= 0.0 && light_intensity.1 <= 0.0 && light_intensity.2 <= 0.0 {
         return ambient
     }
 
-    let light_dot_normal = lightv.dot(&normalv);
+    // average diffuse and specular over every sample on the light
+    let samples = light.samples(point);
+    let mut sum = color(0.0, 0.0, 0.0);
+    for sample in samples.iter() {
+        let (lightv, distance) = light.direction_from(sample, point);
+        let light_dot_normal = lightv.dot(&normalv);
+        if light_dot_normal < 0.0 {
+            continue;
+        }
+        let falloff = light.falloff(distance);
 
-    let diffuse: Vec4;
-    let specular: Vec4;
-    if light_dot_normal < 0.0 {
-        diffuse = color(0.0, 0.0, 0.0);
-        specular = color(0.0, 0.0, 0.0);
-    } else {
-        diffuse = &(&effective_color * material.diffuse) * light_dot_normal;
+        let diffuse = &(&effective_color * material.diffuse) * (light_dot_normal * falloff);
+        sum = &sum + &diffuse;
 
         let reflectv = (-lightv).reflect(&normalv);
         let reflect_dot_eye = reflectv.dot(&eyev);
-
-        if reflect_dot_eye <= 0.0 {
-            specular = color(0.0, 0.0, 0.0);
-        } else {
+        if reflect_dot_eye > 0.0 {
             let factor = reflect_dot_eye.powf(material.shininess);
-            specular = &(&light.intensity * material.specular) * factor;
+            let specular = &(&light.intensity * material.specular) * (factor * falloff);
+            sum = &sum + &specular;
         }
     }
-    &(&specular + &ambient) + &diffuse
+    &ambient + &(&(&sum * &light_intensity) * (1.0 / samples.len() as f32))
 }
@@ -9,5 +9,12 @@ pub mod world;
 pub mod computations;
 pub mod camera;
 pub mod shape;
+pub mod shapes;
 pub mod pattern;
-pub mod patterns;
\ No newline at end of file
+pub mod patterns;
+pub mod obj_parser;
+pub mod mtl_parser;
+pub mod bounds;
+pub mod bvh;
+pub mod random;
+pub mod noise;
\ No newline at end of file
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use crate::implementations::{
+    tuples::*,
+    material::*,
+    obj_parser::parse_floats,
+    patterns::texture_pattern::TexturePattern
+};
+
+// Wavefront MTL material library loader, maps the Phong-style
+// parameters onto Material
+#[derive(Debug)]
+pub struct MtlParser {
+    pub materials: HashMap<String, Material>,
+    // lines that were unsupported or malformed and were skipped
+    pub ignored_lines: usize,
+    // material that statements currently apply to
+    current_material: Option<String>
+}
+
+impl MtlParser {
+    fn new() -> MtlParser {
+        MtlParser {
+            materials: HashMap::new(),
+            ignored_lines: 0,
+            current_material: None
+        }
+    }
+
+    // returns whether the line was understood, texture paths are
+    // resolved relative to base_dir
+    fn parse_line(&mut self, line: &str, base_dir: &Path) -> bool {
+        let mut args = line.split_whitespace();
+        let command = match args.next() {
+            Some(command) => command,
+            None => return true
+        };
+        let args: Vec<&str> = args.collect();
+
+        if command.starts_with('#') {
+            return true
+        }
+        if command == "newmtl" {
+            if args.is_empty() {
+                return false
+            }
+            let name = args.join(" ");
+            self.materials.insert(name.clone(), Material::new());
+            self.current_material = Some(name);
+            return true
+        }
+
+        let material = match &self.current_material {
+            Some(name) => self.materials.get_mut(name).unwrap(),
+            None => return false
+        };
+        match command {
+            "Kd" => match parse_floats(&args, 3) {
+                Some(v) => {
+                    material.color = color(v[0], v[1], v[2]);
+                    true
+                },
+                None => false
+            },
+            // Material only has scalar ambient and specular terms,
+            // so colored ones are averaged down
+            "Ka" => match parse_floats(&args, 3) {
+                Some(v) => {
+                    material.ambient = (v[0] + v[1] + v[2]) / 3.0;
+                    true
+                },
+                None => false
+            },
+            "Ks" => match parse_floats(&args, 3) {
+                Some(v) => {
+                    material.specular = (v[0] + v[1] + v[2]) / 3.0;
+                    true
+                },
+                None => false
+            },
+            "Ns" => match parse_floats(&args, 1) {
+                Some(v) => {
+                    material.shininess = v[0];
+                    true
+                },
+                None => false
+            },
+            "Ni" => match parse_floats(&args, 1) {
+                Some(v) => {
+                    material.refractive_index = v[0];
+                    true
+                },
+                None => false
+            },
+            // d is opacity, Tr is its inverse
+            "d" => match parse_floats(&args, 1) {
+                Some(v) => {
+                    material.transparency = 1.0 - v[0];
+                    true
+                },
+                None => false
+            },
+            "Tr" => match parse_floats(&args, 1) {
+                Some(v) => {
+                    material.transparency = v[0];
+                    true
+                },
+                None => false
+            },
+            "map_Kd" => {
+                // texture options before the file name aren't supported
+                if args.is_empty() || args[0].starts_with('-') {
+                    return false
+                }
+                // missing and undecodable images are skipped like
+                // any other line that can't be used, file names may
+                // contain spaces
+                let path = base_dir.join(args.join(" "));
+                match TexturePattern::uv_mapped(&path.to_string_lossy()) {
+                    Ok(texture) => {
+                        material.set_pattern(Box::new(texture));
+                        true
+                    },
+                    Err(_) => false
+                }
+            },
+            _ => false
+        }
+    }
+}
+
+pub fn parse_mtl(contents: &str, base_dir: &Path) -> MtlParser {
+    let mut parser = MtlParser::new();
+    for line in contents.lines() {
+        if !parser.parse_line(line, base_dir) {
+            parser.ignored_lines += 1;
+        }
+    }
+    parser
+}
+
+// fails only if the file can't be read, lines that couldn't be used
+// are counted in the result's ignored_lines
+pub fn parse_mtl_file(path: &str) -> io::Result<MtlParser> {
+    let contents = fs::read_to_string(path)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    Ok(parse_mtl(&contents, base_dir))
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use super::{tuples::*, random::Rng};
+
+// Gradient (Perlin) noise in 3 and 4 dimensions. Values are roughly in
+// [-1, 1] and are 0 on every integer lattice point. The lattice is
+// shuffled from a seed, so the same seed always gives the same noise.
+#[derive(Clone)]
+pub struct Noise {
+    seed: u64,
+    // permutation of 0..256 repeated twice so lookups don't need wrapping
+    perm: Vec<u8>
+}
+
+// printing all 512 entries isn't useful, the seed identifies the noise
+impl fmt::Debug for Noise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Noise").field("seed", &self.seed).finish()
+    }
+}
+
+impl PartialEq for Noise {
+    fn eq(&self, other: &Noise) -> bool {
+        self.seed == other.seed
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+// dot product of the offset with one of the 12 cube edge directions
+fn grad3(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+// same for the 32 edge directions of a tesseract
+fn grad4(hash: u8, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let h = hash & 31;
+    let u = if h < 24 { x } else { y };
+    let v = if h < 16 { y } else { z };
+    let t = if h < 8 { z } else { w };
+    (if h & 1 == 0 { u } else { -u })
+        + (if h & 2 == 0 { v } else { -v })
+        + (if h & 4 == 0 { t } else { -t })
+}
+
+// integer lattice cell (wrapped to the table) and offset inside it
+fn split(coord: f32) -> (usize, f32) {
+    let floor = coord.floor();
+    ((floor as i64 & 255) as usize, coord - floor)
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Noise {
+        let mut rng = Rng::new(seed);
+        let mut table: Vec<u8> = (0..=255).collect();
+        // Fisher-Yates shuffle
+        for i in (1..table.len()).rev() {
+            let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+            table.swap(i, j);
+        }
+        let mut perm = table.clone();
+        perm.extend(table);
+        Noise {
+            seed,
+            perm
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn hash(&self, index: usize) -> usize {
+        self.perm[index] as usize
+    }
+
+    // noise at the x, y and z of p
+    pub fn noise3(&self, p: &Vec4) -> f32 {
+        let (xi, x) = split(p.0);
+        let (yi, y) = split(p.1);
+        let (zi, z) = split(p.2);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        // hash each corner of the cell
+        let a = self.hash(xi) + yi;
+        let aa = self.hash(a) + zi;
+        let ab = self.hash(a + 1) + zi;
+        let b = self.hash(xi + 1) + yi;
+        let ba = self.hash(b) + zi;
+        let bb = self.hash(b + 1) + zi;
+
+        // blend the corner gradients
+        let corner = |index: usize, dx: f32, dy: f32, dz: f32| {
+            grad3(self.perm[index], x - dx, y - dy, z - dz)
+        };
+        lerp(w,
+            lerp(v,
+                lerp(u, corner(aa, 0.0, 0.0, 0.0), corner(ba, 1.0, 0.0, 0.0)),
+                lerp(u, corner(ab, 0.0, 1.0, 0.0), corner(bb, 1.0, 1.0, 0.0))
+            ),
+            lerp(v,
+                lerp(u, corner(aa + 1, 0.0, 0.0, 1.0), corner(ba + 1, 1.0, 0.0, 1.0)),
+                lerp(u, corner(ab + 1, 0.0, 1.0, 1.0), corner(bb + 1, 1.0, 1.0, 1.0))
+            )
+        )
+    }
+
+    // noise at the x, y and z of p and a fourth coordinate w, for
+    // instance time to animate 3D noise smoothly
+    pub fn noise4(&self, p: &Vec4, w: f32) -> f32 {
+        let cells = [split(p.0), split(p.1), split(p.2), split(w)];
+        let fades = [fade(cells[0].1), fade(cells[1].1), fade(cells[2].1), fade(cells[3].1)];
+
+        // value at each of the 16 corners, bit i of the corner index
+        // picks the far side of the cell along axis i
+        let mut values = [0.0; 16];
+        for (corner, value) in values.iter_mut().enumerate() {
+            let mut hash = 0;
+            let mut offsets = [0.0; 4];
+            for axis in 0..4 {
+                let step = (corner >> axis) & 1;
+                hash = self.hash(hash + cells[axis].0 + step);
+                offsets[axis] = cells[axis].1 - step as f32;
+            }
+            *value = grad4(hash as u8, offsets[0], offsets[1], offsets[2], offsets[3]);
+        }
+
+        // collapse one axis at a time
+        let mut len = 16;
+        for fade in fades.iter() {
+            len /= 2;
+            for i in 0..len {
+                values[i] = lerp(*fade, values[2 * i], values[2 * i + 1]);
+            }
+        }
+        values[0]
+    }
+
+    // octaves of noise, each at twice the frequency and half the
+    // amplitude of the last, normalized back to roughly [-1, 1]
+    pub fn fractal(&self, p: &Vec4, octaves: usize) -> f32 {
+        self.octaves(octaves, |n| n, |frequency| self.noise3(&(p * frequency)))
+    }
+
+    // like fractal but summing the absolute value of each octave, which
+    // gives sharp creases instead of smooth hills; in [0, 1]
+    pub fn turbulence(&self, p: &Vec4, octaves: usize) -> f32 {
+        self.octaves(octaves, f32::abs, |frequency| self.noise3(&(p * frequency)))
+    }
+
+    // fractal over 4D noise, w is scaled along with p so every octave
+    // changes at its own rate
+    pub fn fractal4(&self, p: &Vec4, w: f32, octaves: usize) -> f32 {
+        self.octaves(octaves, |n| n, |frequency| self.noise4(&(p * frequency), w * frequency))
+    }
+
+    pub fn turbulence4(&self, p: &Vec4, w: f32, octaves: usize) -> f32 {
+        self.octaves(octaves, f32::abs, |frequency| self.noise4(&(p * frequency), w * frequency))
+    }
+
+    // sample gives the noise at a frequency
+    fn octaves(&self, octaves: usize, shape: fn(f32) -> f32, sample: impl Fn(f32) -> f32) -> f32 {
+        let mut sum = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        for _ in 0..octaves.max(1) {
+            sum += amplitude * shape(sample(frequency));
+            total_amplitude += amplitude;
+            frequency *= 2.0;
+            amplitude *= 0.5;
+        }
+        sum / total_amplitude
+    }
+}
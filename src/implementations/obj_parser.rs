@@ -0,0 +1,267 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use crate::implementations::{
+    tuples::*,
+    shape::*,
+    shapes::{group::Group, triangle::Triangle, smooth_triangle::SmoothTriangle},
+    material::*,
+    mtl_parser::*
+};
+
+// Wavefront OBJ loader, turns vertex/face data into triangles grouped
+// by the file's named groups
+#[derive(Debug)]
+pub struct ObjParser {
+    pub vertices: Vec<Vec4>,
+    pub normals: Vec<Vec4>,
+    pub texture_coords: Vec<Vec2>,
+    // faces that appear before any named group
+    pub default_group: Group,
+    pub groups: Vec<(String, Group)>,
+    // every material loaded through mtllib statements
+    pub materials: HashMap<String, Material>,
+    // lines that were unsupported or malformed and were skipped
+    pub ignored_lines: usize,
+    // lines skipped in the material libraries, counted separately since
+    // they belong to other files
+    pub ignored_mtl_lines: usize,
+    // index into groups that faces are currently added to
+    current_group: Option<usize>,
+    // material given to new faces, set by usemtl
+    current_material: Option<Material>
+}
+
+// one corner of a face, indices are already resolved to 0-based
+struct FaceVertex {
+    vertex: usize,
+    texture: Option<usize>,
+    normal: Option<usize>
+}
+
+// OBJ indices are 1-based, negative ones count back from the most
+// recently defined element
+fn resolve_index(index: &str, len: usize) -> Option<usize> {
+    let index = index.parse::<i64>().ok()?;
+    let resolved = if index < 0 {
+        len as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return None
+    }
+    Some(resolved as usize)
+}
+
+pub(crate) fn parse_floats(args: &[&str], count: usize) -> Option<Vec<f32>> {
+    if args.len() < count {
+        return None
+    }
+    args[..count].iter()
+        .map(|arg| arg.parse::<f32>().ok())
+        .collect()
+}
+
+impl ObjParser {
+    fn new() -> ObjParser {
+        ObjParser {
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            texture_coords: Vec::new(),
+            default_group: Group::new(),
+            groups: Vec::new(),
+            materials: HashMap::new(),
+            ignored_lines: 0,
+            ignored_mtl_lines: 0,
+            current_group: None,
+            current_material: None
+        }
+    }
+
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter()
+            .find(|(group_name, _)| group_name == name)
+            .map(|(_, group)| group)
+    }
+
+    // single group holding the default group's faces and every named group
+    pub fn to_group(self) -> Group {
+        let mut group = self.default_group;
+        for (_, named_group) in self.groups {
+            group.add_child(Box::new(named_group));
+        }
+        group
+    }
+
+    fn parse_face_vertex(&self, arg: &str) -> Option<FaceVertex> {
+        // v, v/vt, v//vn or v/vt/vn
+        let mut indices = arg.split('/');
+        let vertex = resolve_index(indices.next()?, self.vertices.len())?;
+        let texture = match indices.next() {
+            Some(texture) if !texture.is_empty() => Some(resolve_index(texture, self.texture_coords.len())?),
+            _ => None
+        };
+        let normal = match indices.next() {
+            Some(normal) if !normal.is_empty() => Some(resolve_index(normal, self.normals.len())?),
+            _ => None
+        };
+        Some(FaceVertex {
+            vertex,
+            texture,
+            normal
+        })
+    }
+
+    // fan triangulation of a convex polygon around its first vertex
+    fn parse_face(&self, args: &[&str]) -> Option<Vec<Box<dyn Shape>>> {
+        if args.len() < 3 {
+            return None
+        }
+        let corners = args.iter()
+            .map(|arg| self.parse_face_vertex(arg))
+            .collect::<Option<Vec<FaceVertex>>>()?;
+
+        let mut triangles: Vec<Box<dyn Shape>> = Vec::new();
+        for index in 1..corners.len() - 1 {
+            let (c1, c2, c3) = (&corners[0], &corners[index], &corners[index + 1]);
+            let p1 = self.vertices[c1.vertex];
+            let p2 = self.vertices[c2.vertex];
+            let p3 = self.vertices[c3.vertex];
+            // texture coordinates are only kept if every corner has them
+            let texture_coords = match (c1.texture, c2.texture, c3.texture) {
+                (Some(t1), Some(t2), Some(t3)) => Some([
+                    self.texture_coords[t1], self.texture_coords[t2], self.texture_coords[t3]
+                ]),
+                _ => None
+            };
+            let triangle: Box<dyn Shape> = match (c1.normal, c2.normal, c3.normal) {
+                (Some(n1), Some(n2), Some(n3)) => {
+                    let mut triangle = SmoothTriangle::new(
+                        p1, p2, p3,
+                        self.normals[n1], self.normals[n2], self.normals[n3]
+                    );
+                    triangle.texture_coords = texture_coords;
+                    Box::new(triangle)
+                },
+                _ => {
+                    let mut triangle = Triangle::new(p1, p2, p3);
+                    triangle.texture_coords = texture_coords;
+                    Box::new(triangle)
+                }
+            };
+            triangles.push(triangle);
+        }
+        Some(triangles)
+    }
+
+    // returns whether the line was understood, material libraries
+    // are resolved relative to base_dir
+    fn parse_line(&mut self, line: &str, base_dir: &Path) -> bool {
+        let mut args = line.split_whitespace();
+        let command = match args.next() {
+            Some(command) => command,
+            // blank lines are fine
+            None => return true
+        };
+        let args: Vec<&str> = args.collect();
+
+        match command {
+            _ if command.starts_with('#') => true,
+            "v" => match parse_floats(&args, 3) {
+                Some(v) => {
+                    self.vertices.push(point(v[0], v[1], v[2]));
+                    true
+                },
+                None => false
+            },
+            "vn" => match parse_floats(&args, 3) {
+                Some(v) => {
+                    self.normals.push(vector(v[0], v[1], v[2]));
+                    true
+                },
+                None => false
+            },
+            "vt" => match parse_floats(&args, 2) {
+                Some(v) => {
+                    self.texture_coords.push(Vec2(v[0], v[1]));
+                    true
+                },
+                None => false
+            },
+            "f" => match self.parse_face(&args) {
+                Some(triangles) => {
+                    let group = match self.current_group {
+                        Some(index) => &mut self.groups[index].1,
+                        None => &mut self.default_group
+                    };
+                    for mut triangle in triangles {
+                        if let Some(material) = &self.current_material {
+                            *triangle.material_mut() = material.clone();
+                        }
+                        group.add_child(triangle);
+                    }
+                    true
+                },
+                None => false
+            },
+            "g" => {
+                let name = args.join(" ");
+                // reopening an existing group appends to it
+                let index = match self.groups.iter().position(|(group_name, _)| *group_name == name) {
+                    Some(index) => index,
+                    None => {
+                        self.groups.push((name, Group::new()));
+                        self.groups.len() - 1
+                    }
+                };
+                self.current_group = Some(index);
+                true
+            },
+            "mtllib" => {
+                if args.is_empty() {
+                    return false
+                }
+                // every library has to load for the line to count as understood
+                let mut loaded = true;
+                for library in args {
+                    let path = base_dir.join(library);
+                    match parse_mtl_file(&path.to_string_lossy()) {
+                        Ok(library) => {
+                            self.ignored_mtl_lines += library.ignored_lines;
+                            self.materials.extend(library.materials);
+                        },
+                        Err(_) => loaded = false
+                    }
+                }
+                loaded
+            },
+            "usemtl" => match self.materials.get(&args.join(" ")) {
+                Some(material) => {
+                    self.current_material = Some(material.clone());
+                    true
+                },
+                None => false
+            },
+            _ => false
+        }
+    }
+}
+
+// material libraries referenced by contents are resolved relative to base_dir
+pub fn parse_obj(contents: &str, base_dir: &Path) -> ObjParser {
+    let mut parser = ObjParser::new();
+    for line in contents.lines() {
+        if !parser.parse_line(line, base_dir) {
+            parser.ignored_lines += 1;
+        }
+    }
+    parser
+}
+
+// fails only if the file can't be read, lines that couldn't be used
+// are counted in the result's ignored_lines
+pub fn parse_obj_file(path: &str) -> io::Result<ObjParser> {
+    let contents = fs::read_to_string(path)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    Ok(parse_obj(&contents, base_dir))
+}
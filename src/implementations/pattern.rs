@@ -1,17 +1,51 @@
-use std::fmt::Debug;
+use std::{any::Any, fmt::Debug};
 use super::{matrices::Matrix4x4, shape::*, tuples::*};
 
+// the transform every pattern has regardless of how it colors points
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PatternTransform {
+    // set with set_transform so the inverse stays in sync
+    transform: Matrix4x4,
+    inverse: Matrix4x4
+}
+
+impl PatternTransform {
+    pub fn new() -> PatternTransform {
+        PatternTransform {
+            transform: Matrix4x4::ident(),
+            inverse: Matrix4x4::ident()
+        }
+    }
+}
+
+impl Default for PatternTransform {
+    fn default() -> PatternTransform {
+        PatternTransform::new()
+    }
+}
+
 pub trait PatternClone {
     fn clone_box(&self) -> Box<dyn Pattern>;
+    fn as_any(&self) -> &dyn Any;
+    // false for patterns of different types, otherwise compares fields
+    fn eq_box(&self, other: &dyn Pattern) -> bool;
 }
 
 impl<T> PatternClone for T
 where
-    T: 'static + Pattern + Clone
+    T: 'static + Pattern + Clone + PartialEq
 {
     fn clone_box(&self) -> Box<dyn Pattern> {
         Box::new(self.clone())
     }   
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_box(&self, other: &dyn Pattern) -> bool {
+        other.as_any().downcast_ref::<T>().is_some_and(|other| self == other)
+    }
 }
 
 impl Clone for Box<dyn Pattern> {
@@ -20,16 +54,91 @@ impl Clone for Box<dyn Pattern> {
     }
 }
 
-pub trait Pattern: Debug + PatternClone {
-    fn color_at(&self, shape: &Shape, point: &Vec4) -> Vec4;
-    fn get_transform(&self) -> Matrix4x4;
-    fn eq(&self, other: &dyn Pattern) -> bool;
+impl PartialEq for Box<dyn Pattern + '_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_box(other.as_ref())
+    }
+}
+
+// Send + Sync so worlds can be shared between render threads
+pub trait Pattern: Debug + PatternClone + Send + Sync {
+    // color at a point already in this pattern's own space
+    fn local_color_at(&self, point: &Vec4) -> Vec4;
+
+    fn color_at(&self, shape: &dyn Shape, point: &Vec4) -> Vec4 {
+        self.local_color_at(&transform_point_to_pattern_space(self, shape, point))
+    }
+
+    // color of a pattern nested in another, point is in the parent's
+    // pattern space and the child's own transform is applied on top
+    fn nested_color_at(&self, point: &Vec4) -> Vec4 {
+        self.local_color_at(&(&self.get_inverse_transform() * point))
+    }
+
+    fn transform_data(&self) -> &PatternTransform;
+    fn transform_data_mut(&mut self) -> &mut PatternTransform;
+
+    fn get_transform(&self) -> Matrix4x4 {
+        self.transform_data().transform
+    }
+
+    fn get_inverse_transform(&self) -> Matrix4x4 {
+        self.transform_data().inverse
+    }
+
+    // caches the inverse here rather than inverting for every point
+    fn set_transform(&mut self, transform: Matrix4x4) {
+        let data = self.transform_data_mut();
+        data.transform = transform;
+        data.inverse = transform.invert();
+    }
 }
 
-pub fn transform_point_to_pattern_space<T>(pattern: &T, shape: &Shape, point: &Vec4) -> Vec4 
-    where T: Pattern
+pub fn transform_point_to_pattern_space<T>(pattern: &T, shape: &dyn Shape, point: &Vec4) -> Vec4 
+    where T: Pattern + ?Sized
 {
-    let object_point = &shape.transform.invert() * point;
-    let pattern_point = &pattern.get_transform().invert() * &object_point;
+    let object_point = shape.world_to_object(point);
+    let pattern_point = &pattern.get_inverse_transform() * &object_point;
     pattern_point
-}
\ No newline at end of file
+}
+
+// Colors placed at positions along 0..1, blended linearly in between.
+// Procedural patterns use one to turn a noise value into a color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    // sorted by position
+    pub stops: Vec<(f32, Vec4)>
+}
+
+impl ColorRamp {
+    pub fn new(mut stops: Vec<(f32, Vec4)>) -> ColorRamp {
+        if stops.is_empty() {
+            panic!("A color ramp needs at least one color stop");
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ColorRamp {
+            stops
+        }
+    }
+
+    // straight blend from a at 0 to b at 1
+    pub fn between(a: Vec4, b: Vec4) -> ColorRamp {
+        ColorRamp::new(vec![(0.0, a), (1.0, b)])
+    }
+
+    // positions before the first stop or after the last get its color
+    pub fn color_at(&self, position: f32) -> Vec4 {
+        let first = self.stops[0];
+        if position <= first.0 {
+            return first.1
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, a), (end, b)) = (pair[0], pair[1]);
+            if position <= end {
+                let fraction = (position - start) / (end - start);
+                return &a + &(&(&b - &a) * fraction)
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
@@ -0,0 +1,53 @@
+use super::super::{tuples::*, pattern::*};
+use std::fmt::Debug;
+
+// mixes two patterns at every point, weight is how much of b shows
+// through (0 is all a, 1 is all b)
+#[derive(Debug, Clone)]
+pub struct BlendPattern {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    pub weight: f32,
+    transform: PatternTransform
+}
+
+impl BlendPattern {
+    // even average of both patterns
+    pub fn new(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> BlendPattern {
+        BlendPattern::weighted(a, b, 0.5)
+    }
+
+    pub fn weighted(a: Box<dyn Pattern>, b: Box<dyn Pattern>, weight: f32) -> BlendPattern {
+        BlendPattern {
+            a,
+            b,
+            weight,
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl PartialEq for BlendPattern {
+    fn eq(&self, other: &BlendPattern) -> bool {
+        self.a.eq(&other.a)
+            && self.b.eq(&other.b)
+            && self.weight == other.weight
+            && self.transform == other.transform
+    }
+}
+
+impl Pattern for BlendPattern {
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
+        let a = self.a.nested_color_at(point);
+        let b = self.b.nested_color_at(point);
+        &(&a * (1.0 - self.weight)) + &(&b * self.weight)
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
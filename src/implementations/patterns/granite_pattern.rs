@@ -0,0 +1,42 @@
+use super::super::{tuples::*, pattern::*, noise::Noise};
+use std::fmt::Debug;
+
+// speckled granite: fine turbulence picked out by a ramp, a ramp with
+// a sharp step somewhere gives distinct flecks
+#[derive(Debug, Clone, PartialEq)]
+pub struct GranitePattern {
+    pub ramp: ColorRamp,
+    // size of the speckles, higher makes them smaller
+    pub frequency: f32,
+    pub octaves: usize,
+    pub noise: Noise,
+    transform: PatternTransform
+}
+
+impl GranitePattern {
+    pub fn new(ramp: ColorRamp) -> GranitePattern {
+        GranitePattern {
+            ramp,
+            frequency: 8.0,
+            octaves: 3,
+            noise: Noise::new(0),
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl Pattern for GranitePattern {
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
+        // turbulence rarely gets near 1, stretch it over the whole ramp
+        let speckle = self.noise.turbulence(&(point * self.frequency), self.octaves);
+        self.ramp.color_at((speckle * 2.0).min(1.0))
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
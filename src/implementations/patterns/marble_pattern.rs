@@ -0,0 +1,46 @@
+use super::super::{tuples::*, pattern::*, noise::Noise};
+use std::fmt::Debug;
+
+// veined marble: bands along x bent out of shape by turbulence
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarblePattern {
+    // from the middle of the stone (0) to the middle of a vein (1)
+    pub ramp: ColorRamp,
+    // bands per unit along x
+    pub frequency: f32,
+    // how far the veins wander, 0 gives straight bands
+    pub turbulence: f32,
+    pub octaves: usize,
+    pub noise: Noise,
+    transform: PatternTransform
+}
+
+impl MarblePattern {
+    pub fn new(ramp: ColorRamp) -> MarblePattern {
+        MarblePattern {
+            ramp,
+            frequency: 1.0,
+            turbulence: 2.0,
+            octaves: 4,
+            noise: Noise::new(0),
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl Pattern for MarblePattern {
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
+        let x = point.0 + self.turbulence * self.noise.turbulence(point, self.octaves);
+        // cosine so the veins are thin and the stone between them wide
+        let band = (x * self.frequency * std::f32::consts::PI).cos().abs();
+        self.ramp.color_at(band.powi(8))
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
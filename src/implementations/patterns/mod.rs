@@ -2,4 +2,11 @@ pub mod striped_pattern;
 pub mod gradient_pattern;
 pub mod ring_pattern;
 pub mod checker_pattern;
-pub mod texture_pattern;
\ No newline at end of file
+pub mod texture_pattern;
+pub mod solid_pattern;
+pub mod blend_pattern;
+pub mod perturbed_pattern;
+pub mod marble_pattern;
+pub mod wood_pattern;
+pub mod granite_pattern;
+pub mod worley_pattern;
\ No newline at end of file
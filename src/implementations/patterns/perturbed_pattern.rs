@@ -0,0 +1,57 @@
+use super::super::{tuples::*, pattern::*, noise::Noise};
+use std::fmt::Debug;
+
+// wraps another pattern and moves each point by a little noise before
+// looking it up, so stripes wobble and checkers get ragged edges
+#[derive(Debug, Clone)]
+pub struct PerturbedPattern {
+    pub pattern: Box<dyn Pattern>,
+    // how far points can be pushed, in pattern space
+    pub scale: f32,
+    // more octaves add finer detail to the jitter
+    pub octaves: usize,
+    pub noise: Noise,
+    transform: PatternTransform
+}
+
+impl PerturbedPattern {
+    pub fn new(pattern: Box<dyn Pattern>, scale: f32) -> PerturbedPattern {
+        PerturbedPattern {
+            pattern,
+            scale,
+            octaves: 1,
+            noise: Noise::new(0),
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl PartialEq for PerturbedPattern {
+    fn eq(&self, other: &PerturbedPattern) -> bool {
+        self.pattern.eq(&other.pattern)
+            && self.scale == other.scale
+            && self.octaves == other.octaves
+            && self.noise == other.noise
+            && self.transform == other.transform
+    }
+}
+
+impl Pattern for PerturbedPattern {
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
+        // sample the noise at offset positions so each axis moves
+        // independently of the others
+        let dx = self.noise.fractal(point, self.octaves);
+        let dy = self.noise.fractal(&(point + &vector(31.4, 0.0, 0.0)), self.octaves);
+        let dz = self.noise.fractal(&(point + &vector(0.0, 0.0, 27.1)), self.octaves);
+        let jittered = point + &(&vector(dx, dy, dz) * self.scale);
+        self.pattern.nested_color_at(&jittered)
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
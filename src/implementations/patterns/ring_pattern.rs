@@ -1,28 +1,51 @@
-use super::super::{tuples::*, pattern::*, matrices::Matrix4x4, shape::*};
+use super::super::{tuples::*, pattern::*};
+use super::solid_pattern::SolidPattern;
 use std::fmt::Debug;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct RingPattern {
-    pub a: Vec4,
-    pub b: Vec4,
-    pub transform: Matrix4x4
+    // solid colors are wrapped in a SolidPattern
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: PatternTransform
+}
+
+impl RingPattern {
+    pub fn new(a: Vec4, b: Vec4) -> RingPattern {
+        RingPattern::from_patterns(Box::new(SolidPattern::new(a)), Box::new(SolidPattern::new(b)))
+    }
+
+    pub fn from_patterns(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> RingPattern {
+        RingPattern {
+            a,
+            b,
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl PartialEq for RingPattern {
+    fn eq(&self, other: &RingPattern) -> bool {
+        self.a.eq(&other.a)
+            && self.b.eq(&other.b)
+            && self.transform == other.transform
+    }
 }
 
 impl Pattern for RingPattern {
-    fn color_at(&self, shape: &Shape, point: &Vec4) -> Vec4 {
-        let point = transform_point_to_pattern_space(self, shape, point);
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
         if (point.0.powi(2) + point.2.powi(2)) % 2.0 == 0.0 {
-            self.a
+            self.a.nested_color_at(point)
         } else {
-            self.b
+            self.b.nested_color_at(point)
         }
     }
 
-    fn get_transform(&self) -> Matrix4x4 {
-        self.transform
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
     }
 
-    fn eq(&self, other: &dyn Pattern) -> bool {
-        format!("{:?}", self) == format!("{:?}", other)
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
     }
 }
\ No newline at end of file
@@ -0,0 +1,32 @@
+use super::super::{tuples::*, pattern::*};
+use std::fmt::Debug;
+
+// the same color everywhere, used for the plain colors of other patterns
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SolidPattern {
+    pub color: Vec4,
+    transform: PatternTransform
+}
+
+impl SolidPattern {
+    pub fn new(color: Vec4) -> SolidPattern {
+        SolidPattern {
+            color,
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl Pattern for SolidPattern {
+    fn local_color_at(&self, _point: &Vec4) -> Vec4 {
+        self.color
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
@@ -1,27 +1,51 @@
-use super::super::{tuples::*, pattern::*, matrices::Matrix4x4, shape::*};
+use super::super::{tuples::*, pattern::*};
+use super::solid_pattern::SolidPattern;
 use std::fmt::Debug;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct StripedPattern {
-    pub a: Vec4,
-    pub b: Vec4,
-    pub transform: Matrix4x4
+    // solid colors are wrapped in a SolidPattern
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: PatternTransform
+}
+
+impl StripedPattern {
+    pub fn new(a: Vec4, b: Vec4) -> StripedPattern {
+        StripedPattern::from_patterns(Box::new(SolidPattern::new(a)), Box::new(SolidPattern::new(b)))
+    }
+
+    pub fn from_patterns(a: Box<dyn Pattern>, b: Box<dyn Pattern>) -> StripedPattern {
+        StripedPattern {
+            a,
+            b,
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+// derive can't compare the boxed patterns without moving them
+impl PartialEq for StripedPattern {
+    fn eq(&self, other: &StripedPattern) -> bool {
+        self.a.eq(&other.a)
+            && self.b.eq(&other.b)
+            && self.transform == other.transform
+    }
 }
 
 impl Pattern for StripedPattern {
-    fn color_at(&self, shape: &Shape, point: &Vec4) -> Vec4 {
-        let point = transform_point_to_pattern_space(self, shape, point);
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
         if (point.0.floor() as i32) % 2 == 0 {
-            return self.a
+            return self.a.nested_color_at(point)
         }
-        self.b
+        self.b.nested_color_at(point)
     }
 
-    fn get_transform(&self) -> Matrix4x4 {
-        self.transform
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
     }
 
-    fn eq(&self, other: &dyn Pattern) -> bool {
-        format!("{:?}", self) == format!("{:?}", other)
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
     }
 }
\ No newline at end of file
@@ -1,44 +1,97 @@
-use super::super::{tuples::*, pattern::*, matrices::Matrix4x4, shape::*};
+use super::super::{tuples::*, pattern::*, shape::*};
 use std::fmt::Debug;
-use image::{RgbImage};
+use image::{ImageResult, RgbImage};
 use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TexturePattern {
     pub width: u32,
     pub height: u32,
-    pub image_pixels: RgbImage,
-    pub transform: Matrix4x4,
+    // shared so materials cloned onto every triangle of a mesh
+    // don't each copy the image
+    pub image_pixels: Arc<RgbImage>,
+    transform: PatternTransform,
     pub image_scale_x: f32,
     pub image_scale_y: f32,
     pub z_oriented: bool,
     pub flipped: bool,
-    pub offset_x: f32
+    pub offset_x: f32,
+    // look colors up by the shape's texture coordinates instead of
+    // by position, see uv_mapped
+    pub uv_mapped: bool
+}
+
+fn open_image(image_path: &str) -> ImageResult<RgbImage> {
+    Ok(image::open(Path::new(image_path))?.into_rgb8())
 }
 
 impl TexturePattern {
     pub fn new(image_path: &str, image_scale_x: f32, image_scale_y: f32, offset_x: f32, z_oriented: bool, flipped: bool) -> TexturePattern {
-        let image = image::open(&Path::new(image_path));
-        let image = image.unwrap_or_else(|_| {
+        let rgb_image = open_image(image_path).unwrap_or_else(|_| {
             panic!("Image at {} could not be found!", image_path);
         });
-        let rgb_image = image.into_rgb8();
         TexturePattern {
             width: rgb_image.width(),
             height: rgb_image.height(),
-            image_pixels: rgb_image,
-            transform: Matrix4x4::ident(),
+            image_pixels: Arc::new(rgb_image),
+            transform: PatternTransform::new(),
             image_scale_x,
             image_scale_y,
             z_oriented,
             flipped,
-            offset_x
+            offset_x,
+            uv_mapped: false
         }
     }
+
+    // Image stretched once over the texture coordinates of the shapes it
+    // is used on, (0, 0) at the bottom left and wrapping outside 0..1.
+    // Shapes without texture coordinates get the bottom left pixel.
+    // Unlike new, a missing or undecodable image is returned as an error.
+    pub fn uv_mapped(image_path: &str) -> ImageResult<TexturePattern> {
+        let rgb_image = open_image(image_path)?;
+        Ok(TexturePattern {
+            width: rgb_image.width(),
+            height: rgb_image.height(),
+            image_pixels: Arc::new(rgb_image),
+            transform: PatternTransform::new(),
+            image_scale_x: 1.0,
+            image_scale_y: 1.0,
+            z_oriented: false,
+            flipped: false,
+            offset_x: 0.0,
+            uv_mapped: true
+        })
+    }
+
+    pub fn color_at_uv(&self, uv: &Vec2) -> Vec4 {
+        let u = uv.0 - uv.0.floor();
+        let v = uv.1 - uv.1.floor();
+        // image rows run top to bottom
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = (((1.0 - v) * self.height as f32) as u32).min(self.height - 1);
+        self.pixel_color(x, y)
+    }
+
+    fn pixel_color(&self, x: u32, y: u32) -> Vec4 {
+        let pixel = self.image_pixels.get_pixel(x, y);
+        color(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0)
+    }
 }
 
 impl Pattern for TexturePattern {
-    fn color_at(&self, _shape: &Shape, point: &Vec4) -> Vec4 {
+    // looked up by world position or the shape's texture coordinates
+    // rather than in object space
+    fn color_at(&self, shape: &dyn Shape, point: &Vec4) -> Vec4 {
+        if self.uv_mapped {
+            let uv = shape.texture_coords_at(point).unwrap_or(Vec2(0.0, 0.0));
+            return self.color_at_uv(&uv)
+        }
+        self.local_color_at(point)
+    }
+
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
         let mut first_coord = ((point.0.abs() + self.offset_x) * self.image_scale_x) as u32 % self.width;
         let mut second_coord = match self.z_oriented {
             true => (point.2.abs() * self.image_scale_y) as u32 % self.height,
@@ -48,16 +101,14 @@ impl Pattern for TexturePattern {
             second_coord = self.height - 1 - second_coord;
             first_coord = self.width - 1 - first_coord;
         }
-        let pixel = self.image_pixels
-            .get_pixel(first_coord, second_coord);
-        color(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0)
+        self.pixel_color(first_coord, second_coord)
     }
 
-    fn get_transform(&self) -> Matrix4x4 {
-        self.transform
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
     }
 
-    fn eq(&self, other: &dyn Pattern) -> bool {
-        format!("{:?}", self) == format!("{:?}", other)
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
     }
 }
\ No newline at end of file
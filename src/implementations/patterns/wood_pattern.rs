@@ -0,0 +1,49 @@
+use super::super::{tuples::*, pattern::*, noise::Noise};
+use std::fmt::Debug;
+
+// wood grain: rings around the y axis, pushed in and out by noise
+#[derive(Debug, Clone, PartialEq)]
+pub struct WoodPattern {
+    // across each ring, from its inside edge (0) to its outside edge (1)
+    pub ramp: ColorRamp,
+    // rings per unit of distance from the y axis
+    pub frequency: f32,
+    // how much the rings wobble, 0 gives perfect circles
+    pub ring_noise: f32,
+    // scale of the wobble, higher makes it more irregular
+    pub noise_frequency: f32,
+    pub octaves: usize,
+    pub noise: Noise,
+    transform: PatternTransform
+}
+
+impl WoodPattern {
+    pub fn new(ramp: ColorRamp) -> WoodPattern {
+        WoodPattern {
+            ramp,
+            frequency: 4.0,
+            ring_noise: 0.1,
+            noise_frequency: 2.0,
+            octaves: 2,
+            noise: Noise::new(0),
+            transform: PatternTransform::new()
+        }
+    }
+}
+
+impl Pattern for WoodPattern {
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
+        let wobble = self.ring_noise * self.noise.fractal(&(point * self.noise_frequency), self.octaves);
+        let distance = (point.0.powi(2) + point.2.powi(2)).sqrt() + wobble;
+        let rings = distance * self.frequency;
+        self.ramp.color_at(rings - rings.floor())
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
@@ -0,0 +1,163 @@
+use super::super::{tuples::*, pattern::*, random::Rng};
+use std::fmt::Debug;
+
+// which distance the pattern shows
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WorleyOutput {
+    // to the nearest feature point, round cells
+    F1,
+    // to the second nearest
+    F2,
+    // dark along the borders between cells, like cracks
+    F2MinusF1
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DistanceMetric {
+    Euclidean,
+    // diamond shaped cells
+    Manhattan,
+    // square cells
+    Chebyshev
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, a: &Vec4, b: &Vec4) -> f32 {
+        let (dx, dy, dz) = ((a.0 - b.0).abs(), (a.1 - b.1).abs(), (a.2 - b.2).abs());
+        match self {
+            DistanceMetric::Euclidean => (dx * dx + dy * dy + dz * dz).sqrt(),
+            DistanceMetric::Manhattan => dx + dy + dz,
+            DistanceMetric::Chebyshev => dx.max(dy).max(dz)
+        }
+    }
+}
+
+// Cellular (Worley) noise: every unit cube of pattern space holds one
+// randomly placed feature point, and the color comes from the distance
+// to the nearest ones. Works in 3D so it wraps around curved shapes
+// without seams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorleyPattern {
+    // maps the chosen distance, in cell widths, to a color
+    pub ramp: ColorRamp,
+    pub output: WorleyOutput,
+    pub metric: DistanceMetric,
+    // tint each cell with its own random color
+    pub cell_colors: bool,
+    // picks the feature point positions and cell colors
+    pub seed: u64,
+    transform: PatternTransform
+}
+
+impl WorleyPattern {
+    pub fn new(ramp: ColorRamp) -> WorleyPattern {
+        WorleyPattern {
+            ramp,
+            output: WorleyOutput::F1,
+            metric: DistanceMetric::Euclidean,
+            cell_colors: false,
+            seed: 0,
+            transform: PatternTransform::new()
+        }
+    }
+
+    // same sequence every time for a given cell and seed
+    fn cell_rng(&self, cell: (i64, i64, i64)) -> Rng {
+        Rng::new(
+            self.seed
+                ^ (cell.0 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (cell.1 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+                ^ (cell.2 as u64).wrapping_mul(0x1656_67B1_9E37_79F9)
+        )
+    }
+
+    // the randomly placed point inside a unit cell of pattern space
+    pub fn feature_point(&self, cell: (i64, i64, i64)) -> Vec4 {
+        let mut rng = self.cell_rng(cell);
+        point(
+            cell.0 as f32 + rng.next_f32(),
+            cell.1 as f32 + rng.next_f32(),
+            cell.2 as f32 + rng.next_f32()
+        )
+    }
+
+    fn cell_color(&self, cell: (i64, i64, i64)) -> Vec4 {
+        let mut rng = self.cell_rng(cell);
+        // skip the draws used for the feature point
+        for _ in 0..3 {
+            rng.next_f32();
+        }
+        color(rng.next_f32(), rng.next_f32(), rng.next_f32())
+    }
+
+    // F1 and F2 at a point in pattern space, and the cell owning the
+    // nearest feature point. F2 is only exact if with_f2 is set.
+    fn search(&self, point: &Vec4, with_f2: bool) -> (f32, f32, (i64, i64, i64)) {
+        let home = (point.0.floor() as i64, point.1.floor() as i64, point.2.floor() as i64);
+        let mut f1 = f32::INFINITY;
+        let mut f2 = f32::INFINITY;
+        let mut nearest = home;
+        // Cells are visited in rings around the home cell. Every cell in
+        // ring r is at least r - 1 away along some axis, and so in every
+        // metric, so once that reaches the distance the output needs no
+        // further ring can change it.
+        let mut ring: i64 = 0;
+        loop {
+            let needed = if with_f2 { f2 } else { f1 };
+            if (ring - 1) as f32 >= needed {
+                break;
+            }
+            for dx in -ring..=ring {
+                for dy in -ring..=ring {
+                    for dz in -ring..=ring {
+                        // inner rings were already visited
+                        if dx.abs().max(dy.abs()).max(dz.abs()) != ring {
+                            continue;
+                        }
+                        let cell = (home.0 + dx, home.1 + dy, home.2 + dz);
+                        let distance = self.metric.distance(point, &self.feature_point(cell));
+                        if distance < f1 {
+                            f2 = f1;
+                            f1 = distance;
+                            nearest = cell;
+                        } else if distance < f2 {
+                            f2 = distance;
+                        }
+                    }
+                }
+            }
+            ring += 1;
+        }
+        (f1, f2, nearest)
+    }
+
+    // F1 and F2 at a point in pattern space
+    pub fn distances(&self, point: &Vec4) -> (f32, f32) {
+        let (f1, f2, _) = self.search(point, true);
+        (f1, f2)
+    }
+}
+
+impl Pattern for WorleyPattern {
+    fn local_color_at(&self, point: &Vec4) -> Vec4 {
+        let (f1, f2, cell) = self.search(point, self.output != WorleyOutput::F1);
+        let value = match self.output {
+            WorleyOutput::F1 => f1,
+            WorleyOutput::F2 => f2,
+            WorleyOutput::F2MinusF1 => f2 - f1
+        };
+        let color = self.ramp.color_at(value);
+        if self.cell_colors {
+            return &color * &self.cell_color(cell)
+        }
+        color
+    }
+
+    fn transform_data(&self) -> &PatternTransform {
+        &self.transform
+    }
+
+    fn transform_data_mut(&mut self) -> &mut PatternTransform {
+        &mut self.transform
+    }
+}
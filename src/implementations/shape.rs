@@ -1,113 +1,319 @@
+use std::{
+    any::Any,
+    fmt::Debug,
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
 use crate::implementations::{
     tuples::*,
     matrices::*,
     material::*,
     intersection::*,
-    ray::*
+    ray::*,
+    bounds::*
 };
 
-pub fn glass_sphere() -> Shape {
-    let mut s = Shape::new(ShapeType::Sphere);
-    s.material.transparency = 1.0;
-    s.material.refractive_index = 1.5;
-    s
+static NEXT_SHAPE_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Identifies a single shape. Cloning a shape makes a separate object, so
+// the clone is given a new id rather than sharing the original's.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct ShapeId(usize);
+
+impl ShapeId {
+    fn next() -> ShapeId {
+        ShapeId(NEXT_SHAPE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Clone for ShapeId {
+    fn clone(&self) -> ShapeId {
+        ShapeId::next()
+    }
+}
+
+// Which kinds of rays see a shape. Shadow rays pass straight through
+// shapes that don't cast shadows.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Visibility {
+    pub casts_shadow: bool,
+    pub receives_shadow: bool,
+    pub visible_to_camera: bool,
+    // seen by reflected and refracted rays
+    pub visible_in_reflections: bool
+}
+
+impl Visibility {
+    pub fn new() -> Visibility {
+        Visibility {
+            casts_shadow: true,
+            receives_shadow: true,
+            visible_to_camera: true,
+            visible_in_reflections: true
+        }
+    }
+
+    // only what both allow
+    pub fn combine(&self, other: &Visibility) -> Visibility {
+        Visibility {
+            casts_shadow: self.casts_shadow && other.casts_shadow,
+            receives_shadow: self.receives_shadow && other.receives_shadow,
+            visible_to_camera: self.visible_to_camera && other.visible_to_camera,
+            visible_in_reflections: self.visible_in_reflections && other.visible_in_reflections
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum ShapeType {
-    Plane,
-    Sphere
+impl Default for Visibility {
+    fn default() -> Visibility {
+        Visibility::new()
+    }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Shape {
-    pub origin: Vec4,
-    pub transform: Matrix4x4,
+// everything a shape has regardless of its geometry
+#[derive(Debug, Clone)]
+pub struct ShapeData {
+    id: ShapeId,
+    // set with set_transform so the inverses below stay in sync
+    transform: Matrix4x4,
+    inverse: Matrix4x4,
+    // inverse of the shape's transform combined with every enclosing
+    // group's, and its transpose for normals
+    world_inverse: Matrix4x4,
+    world_inverse_transpose: Matrix4x4,
+    // combined inverse of every enclosing group's transform
+    parent_inverse: Matrix4x4,
     pub material: Material,
-    pub shape_type: ShapeType
+    // set with set_visibility so children stay in sync
+    visibility: Visibility,
+    // combined visibility of every enclosing group
+    parent_visibility: Visibility
 }
 
-impl Shape {
-    pub fn new(shape_type: ShapeType) -> Shape {
-        // unit sphere with identity transform matrix
-        Shape {
-            origin: point(0.0, 0.0, 0.0),
+impl ShapeData {
+    pub fn new() -> ShapeData {
+        ShapeData {
+            id: ShapeId::next(),
             transform: Matrix4x4::ident(),
+            inverse: Matrix4x4::ident(),
+            world_inverse: Matrix4x4::ident(),
+            world_inverse_transpose: Matrix4x4::ident(),
+            parent_inverse: Matrix4x4::ident(),
             material: Material::new(),
-            shape_type
+            visibility: Visibility::new(),
+            parent_visibility: Visibility::new()
         }
     }
 
-    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
-        match self.shape_type {
-            ShapeType::Sphere => {
-                // calculate ray intersection
-                // solutions via quadratic formula
-                // get discriminant to get number of solutions 
-                // to return
-                let sphere_to_ray = &ray.origin - &self.origin;
-                let a = ray.direction.dot(&ray.direction);
-                let b = &ray.direction.dot(&sphere_to_ray) * 2.0;
-                let c = sphere_to_ray.dot(&sphere_to_ray) - 1.0;
-                let discriminant = b.powi(2) as f32 - 4.0 * a * c;
-                let discrim_sqrt = discriminant.sqrt();
-                // TODO: Make more DRY with closures
-                if fequals(discriminant, 0.0) {
-                    let i1 = Intersection {
-                        object: self,
-                        t: (-b - discrim_sqrt) / (2.0 * a)
-                    };
-                    vec![i1]
-                } else {
-                    if discriminant > 0.0 {
-                        let i1 = Intersection {
-                            object: self,
-                            t: (-b - discrim_sqrt) / (2.0 * a)
-                        };
-                        let i2 = Intersection {
-                            object: self,
-                            t: (-b + discrim_sqrt) / (2.0 * a)
-                        };
-                        vec![i1, i2]
-                    } else {
-                        vec![] as Vec<Intersection>
-                    }         
-                }
-            },
-            ShapeType::Plane => {
-                if ray.direction.1.abs() < EPSILON {
-                    return vec![]
-                }
-        
-                let t = -ray.origin.1 / ray.direction.1;
-                vec![
-                    Intersection {
-                        object: self,
-                        t
-                    }
-                ]
-            }
+    pub(crate) fn world_inverse(&self) -> &Matrix4x4 {
+        &self.world_inverse
+    }
+
+    fn update_world_inverse(&mut self) {
+        self.world_inverse = &self.inverse * &self.parent_inverse;
+        self.world_inverse_transpose = self.world_inverse.transpose();
+    }
+}
+
+impl Default for ShapeData {
+    fn default() -> ShapeData {
+        ShapeData::new()
+    }
+}
+
+pub trait ShapeClone {
+    fn clone_box(&self) -> Box<dyn Shape>;
+    // lets callers get back the concrete type of a boxed shape
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> ShapeClone for T
+where
+    T: 'static + Shape + Clone
+{
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn Shape> {
+    fn clone(&self) -> Box<dyn Shape> {
+        self.clone_box()
+    }
+}
+
+// shapes are equal only to themselves, comparing ids instead of geometry
+// and materials keeps this cheap wherever intersections are compared
+impl PartialEq for dyn Shape + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+// Implemented by every primitive, see the shapes module. Geometry lives
+// in object space: rays passed to local_intersect and points passed to
+// local_normal_at have already been through the shape's transform.
+// Send + Sync so worlds can be shared between render threads
+pub trait Shape: Debug + ShapeClone + Send + Sync {
+    fn data(&self) -> &ShapeData;
+    fn data_mut(&mut self) -> &mut ShapeData;
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>>;
+    // hit is the intersection the point came from, for shapes that need
+    // more than the point (like smooth triangles' u/v)
+    fn local_normal_at(&self, point: &Vec4, hit: &Intersection) -> Vec4;
+    // bounds of the shape in its own object space
+    fn bounds(&self) -> BoundingBox;
+
+    // only groups and CSG shapes have children
+    fn children(&self) -> &[Box<dyn Shape>] {
+        &[]
+    }
+
+    // children that may be moved or replaced, so every hierarchy built
+    // over them or inside them is dropped first
+    fn children_mut(&mut self) -> &mut [Box<dyn Shape>] {
+        self.invalidate_bvh();
+        self.children_mut_keeping_bvh()
+    }
+
+    // for changes that can't move children relative to this shape, like
+    // passing down an enclosing group's transform
+    fn children_mut_keeping_bvh(&mut self) -> &mut [Box<dyn Shape>] {
+        &mut []
+    }
+
+    // Build bounding volume hierarchies for every group nested inside this
+    // shape. Needs to be called again after children are moved.
+    fn build_bvh(&mut self) {
+        for child in self.children_mut_keeping_bvh() {
+            child.build_bvh();
         }
-        
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
-        let local_ray = ray.transform(&self.transform.invert());
-        self.local_intersect(&local_ray)
+    // drop the hierarchies of every group nested inside this shape,
+    // intersect tests every child until build_bvh is called again
+    fn invalidate_bvh(&mut self) {
+        for child in self.children_mut_keeping_bvh() {
+            child.invalidate_bvh();
+        }
+    }
+
+    fn id(&self) -> &ShapeId {
+        &self.data().id
+    }
+
+    fn material(&self) -> &Material {
+        &self.data().material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.data_mut().material
+    }
+
+    fn transform(&self) -> &Matrix4x4 {
+        &self.data().transform
+    }
+
+    fn inverse(&self) -> &Matrix4x4 {
+        &self.data().inverse
     }
 
-    fn local_normal_at(&self, point: &Vec4) -> Vec4 {
-        match self.shape_type {
-            ShapeType::Plane => vector(0.0, 1.0, 0.0),
-            ShapeType::Sphere => (point - &self.origin).normalize()
+    // inverts the transform once here instead of for every ray, and
+    // updates the cached matrices of any children
+    fn set_transform(&mut self, transform: Matrix4x4) {
+        let data = self.data_mut();
+        data.transform = transform;
+        data.inverse = transform.invert();
+        self.update_world_inverse();
+    }
+
+    // called by groups on shapes added to them
+    fn set_parent_inverse(&mut self, parent_inverse: Matrix4x4) {
+        self.data_mut().parent_inverse = parent_inverse;
+        self.update_world_inverse();
+    }
+
+    fn update_world_inverse(&mut self) {
+        self.data_mut().update_world_inverse();
+        let world_inverse = self.data().world_inverse;
+        for child in self.children_mut_keeping_bvh() {
+            child.set_parent_inverse(world_inverse);
         }
     }
 
-    pub fn normal_at(&self, point: &Vec4) -> Vec4 {
-        let local_point = &self.transform.invert() * point;
-        let local_normal = self.local_normal_at(&local_point);
-        let mut world_normal = &self.transform.invert().transpose() * &local_normal;
-        world_normal.3 = 0.0;
-        world_normal.normalize()
+    // the shape's own visibility, see world_visibility for what is
+    // used when rendering
+    fn visibility(&self) -> Visibility {
+        self.data().visibility
+    }
+
+    // applies to every shape nested inside this one as well
+    fn set_visibility(&mut self, visibility: Visibility) {
+        self.data_mut().visibility = visibility;
+        self.update_world_visibility();
+    }
+
+    // called by groups on shapes added to them
+    fn set_parent_visibility(&mut self, parent_visibility: Visibility) {
+        self.data_mut().parent_visibility = parent_visibility;
+        self.update_world_visibility();
     }
-}
\ No newline at end of file
+
+    fn update_world_visibility(&mut self) {
+        let world_visibility = self.world_visibility();
+        for child in self.children_mut_keeping_bvh() {
+            child.set_parent_visibility(world_visibility);
+        }
+    }
+
+    // visibility combined with every enclosing group's, checked on the
+    // primitive that is hit
+    fn world_visibility(&self) -> Visibility {
+        self.data().visibility.combine(&self.data().parent_visibility)
+    }
+
+    // bounds of the shape in the space of whatever contains it
+    fn parent_space_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.transform())
+    }
+
+    // whether other is this shape or any shape nested inside it
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.id() == other.id() || self.children().iter().any(|child| child.includes(other))
+    }
+
+    // convert a world space point into this shape's object space,
+    // through every enclosing group
+    fn world_to_object(&self, point: &Vec4) -> Vec4 {
+        &self.data().world_inverse * point
+    }
+
+    // convert an object space normal into world space,
+    // through every enclosing group
+    fn normal_to_world(&self, normal: &Vec4) -> Vec4 {
+        let mut normal = &self.data().world_inverse_transpose * normal;
+        normal.3 = 0.0;
+        normal.normalize()
+    }
+
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let local_ray = ray.transform(self.inverse());
+        self.local_intersect(&local_ray)
+    }
+
+    // texture coordinates at a world space point on the shape, only
+    // shapes loaded with them (like OBJ triangles with vt) have any
+    fn texture_coords_at(&self, _point: &Vec4) -> Option<Vec2> {
+        None
+    }
+
+    fn normal_at(&self, point: &Vec4, hit: &Intersection) -> Vec4 {
+        let local_point = self.world_to_object(point);
+        let local_normal = self.local_normal_at(&local_point, hit);
+        self.normal_to_world(&local_normal)
+    }
+}
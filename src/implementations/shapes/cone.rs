@@ -0,0 +1,109 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+use super::cylinder::check_cap;
+
+// double-napped cone x^2 + z^2 = y^2 with its apex at the origin,
+// truncated and capped the same way as a cylinder
+#[derive(Debug, Clone)]
+pub struct Cone {
+    data: ShapeData,
+    pub minimum: f32,
+    pub maximum: f32,
+    pub closed: bool
+}
+
+impl Cone {
+    pub fn new(minimum: f32, maximum: f32, closed: bool) -> Cone {
+        Cone {
+            data: ShapeData::new(),
+            minimum,
+            maximum,
+            closed
+        }
+    }
+}
+
+impl Shape for Cone {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut intersections: Vec<Intersection> = Vec::new();
+
+        let a = ray.direction.0.powi(2) - ray.direction.1.powi(2) + ray.direction.2.powi(2);
+        let b = 2.0 * ray.origin.0 * ray.direction.0
+            - 2.0 * ray.origin.1 * ray.direction.1
+            + 2.0 * ray.origin.2 * ray.direction.2;
+        let c = ray.origin.0.powi(2) - ray.origin.1.powi(2) + ray.origin.2.powi(2);
+
+        let mut wall_hits: Vec<f32> = Vec::new();
+        if fequals(a, 0.0) {
+            // ray is parallel to one of the nappes, so the
+            // quadratic degenerates and there is at most one hit
+            if !fequals(b, 0.0) {
+                wall_hits.push(-c / (2.0 * b));
+            }
+        } else {
+            let discriminant = b.powi(2) - 4.0 * a * c;
+            // tangent rays can land just below zero from round-off
+            if discriminant >= 0.0 || fequals(discriminant, 0.0) {
+                let discrim_sqrt = discriminant.max(0.0).sqrt();
+                let mut t0 = (-b - discrim_sqrt) / (2.0 * a);
+                let mut t1 = (-b + discrim_sqrt) / (2.0 * a);
+                if t0 > t1 {
+                    std::mem::swap(&mut t0, &mut t1);
+                }
+                wall_hits.push(t0);
+                wall_hits.push(t1);
+            }
+        }
+
+        for t in wall_hits {
+            let y = ray.origin.1 + t * ray.direction.1;
+            if self.minimum < y && y < self.maximum {
+                intersections.push(Intersection::new(t, self));
+            }
+        }
+
+        // cap radius is the distance of the cap from the apex
+        if self.closed && !fequals(ray.direction.1, 0.0) {
+            for y in [self.minimum, self.maximum].iter() {
+                let t = (y - ray.origin.1) / ray.direction.1;
+                if check_cap(ray, t, y.abs()) {
+                    intersections.push(Intersection::new(t, self));
+                }
+            }
+        }
+        intersections
+    }
+
+    fn local_normal_at(&self, point: &Vec4, _hit: &Intersection) -> Vec4 {
+        let dist = point.0.powi(2) + point.2.powi(2);
+        if dist < self.maximum.powi(2) && point.1 >= self.maximum - EPSILON {
+            vector(0.0, 1.0, 0.0)
+        } else if dist < self.minimum.powi(2) && point.1 <= self.minimum + EPSILON {
+            vector(0.0, -1.0, 0.0)
+        } else if fequals(dist, 0.0) && fequals(point.1, 0.0) {
+            // the apex has no well-defined normal, so point out
+            // of the tip (upwards if only the lower nappe exists)
+            if self.maximum <= 0.0 {
+                vector(0.0, 1.0, 0.0)
+            } else {
+                vector(0.0, -1.0, 0.0)
+            }
+        } else {
+            // wall normals lean away from the apex
+            let y = if point.1 > 0.0 { -dist.sqrt() } else { dist.sqrt() };
+            vector(point.0, y, point.2)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let radius = self.minimum.abs().max(self.maximum.abs());
+        BoundingBox::new(point(-radius, self.minimum, -radius), point(radius, self.maximum, radius))
+    }
+}
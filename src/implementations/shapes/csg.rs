@@ -0,0 +1,111 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference
+}
+
+impl CsgOperation {
+    // whether a hit on the left (or right) child survives, given whether
+    // the ray is currently inside the left and right children
+    pub fn intersection_allowed(&self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left)
+        }
+    }
+}
+
+// constructive solid geometry combining left and right, which become
+// the shape's two children
+#[derive(Debug, Clone)]
+pub struct Csg {
+    data: ShapeData,
+    pub operation: CsgOperation,
+    children: Vec<Box<dyn Shape>>
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, mut left: Box<dyn Shape>, mut right: Box<dyn Shape>) -> Csg {
+        let data = ShapeData::new();
+        left.set_parent_inverse(*data.world_inverse());
+        right.set_parent_inverse(*data.world_inverse());
+        left.set_parent_visibility(Visibility::new());
+        right.set_parent_visibility(Visibility::new());
+        Csg {
+            data,
+            operation,
+            children: vec![left, right]
+        }
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.children[0].as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.children[1].as_ref()
+    }
+
+    // keep only the sorted intersections that lie on the surface of the
+    // combined shape, marking this shape as the volume they bound
+    pub fn filter_intersections<'a>(&'a self, intersections: Vec<Intersection<'a>>) -> Vec<Intersection<'a>> {
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut result: Vec<Intersection> = Vec::new();
+        for mut i in intersections {
+            let left_hit = self.left().includes(i.object);
+            if self.operation.intersection_allowed(left_hit, in_left, in_right) {
+                i.container = self;
+                result.push(i);
+            }
+
+            if left_hit {
+                in_left = !in_left;
+            } else {
+                in_right = !in_right;
+            }
+        }
+        result
+    }
+}
+
+impl Shape for Csg {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    fn children_mut_keeping_bvh(&mut self) -> &mut [Box<dyn Shape>] {
+        &mut self.children
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut intersections = self.left().intersect(ray);
+        intersections.extend(self.right().intersect(ray));
+        sort_intersections(&mut intersections);
+        self.filter_intersections(intersections)
+    }
+
+    fn local_normal_at(&self, _point: &Vec4, _hit: &Intersection) -> Vec4 {
+        panic!("CSG shapes have no normal, normal_at should be called on the child that was hit")
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for child in &self.children {
+            bounds.merge(&child.parent_space_bounds());
+        }
+        bounds
+    }
+}
@@ -0,0 +1,85 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+
+// axis-aligned cube from -1 to 1 on every axis
+#[derive(Debug, Clone)]
+pub struct Cube {
+    data: ShapeData
+}
+
+impl Cube {
+    pub fn new() -> Cube {
+        Cube {
+            data: ShapeData::new()
+        }
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Cube {
+        Cube::new()
+    }
+}
+
+// distances along a ray to the two planes of a unit cube slab on one axis,
+// returned in (near, far) order
+fn check_axis(origin: f32, direction: f32) -> (f32, f32) {
+    let tmin_numerator = -1.0 - origin;
+    let tmax_numerator = 1.0 - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (tmin_numerator * f32::INFINITY, tmax_numerator * f32::INFINITY)
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        // slab test: intersect ray with each pair of parallel
+        // planes, the largest entry and smallest exit are the hits
+        let (xtmin, xtmax) = check_axis(ray.origin.0, ray.direction.0);
+        let (ytmin, ytmax) = check_axis(ray.origin.1, ray.direction.1);
+        let (ztmin, ztmax) = check_axis(ray.origin.2, ray.direction.2);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+        if tmin > tmax {
+            return vec![]
+        }
+
+        vec![
+            Intersection::new(tmin, self),
+            Intersection::new(tmax, self)
+        ]
+    }
+
+    fn local_normal_at(&self, point: &Vec4, _hit: &Intersection) -> Vec4 {
+        // normal points out of the face with the largest component
+        let maxc = point.0.abs().max(point.1.abs()).max(point.2.abs());
+        if maxc == point.0.abs() {
+            vector(point.0, 0.0, 0.0)
+        } else if maxc == point.1.abs() {
+            vector(0.0, point.1, 0.0)
+        } else {
+            vector(0.0, 0.0, point.2)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
+    }
+}
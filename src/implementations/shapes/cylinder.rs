@@ -0,0 +1,100 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+
+// unit radius cylinder around the y axis, truncated to
+// minimum < y < maximum and optionally capped at both ends
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    data: ShapeData,
+    pub minimum: f32,
+    pub maximum: f32,
+    pub closed: bool
+}
+
+impl Cylinder {
+    pub fn new(minimum: f32, maximum: f32, closed: bool) -> Cylinder {
+        Cylinder {
+            data: ShapeData::new(),
+            minimum,
+            maximum,
+            closed
+        }
+    }
+}
+
+// whether the point at t along the ray lies within the given radius
+// of the y axis, used for the end caps of cylinders and cones
+// (EPSILON keeps rays hitting the rim from slipping between cap and wall)
+pub(crate) fn check_cap(ray: &Ray, t: f32, radius: f32) -> bool {
+    let x = ray.origin.0 + t * ray.direction.0;
+    let z = ray.origin.2 + t * ray.direction.2;
+    x.powi(2) + z.powi(2) <= radius.powi(2) + EPSILON
+}
+
+impl Shape for Cylinder {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut intersections: Vec<Intersection> = Vec::new();
+
+        // ray is not parallel to the y axis, so it may hit the walls
+        let a = ray.direction.0.powi(2) + ray.direction.2.powi(2);
+        if !fequals(a, 0.0) {
+            let b = 2.0 * ray.origin.0 * ray.direction.0
+                + 2.0 * ray.origin.2 * ray.direction.2;
+            let c = ray.origin.0.powi(2) + ray.origin.2.powi(2) - 1.0;
+            let discriminant = b.powi(2) - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return intersections
+            }
+
+            let discrim_sqrt = discriminant.sqrt();
+            let mut t0 = (-b - discrim_sqrt) / (2.0 * a);
+            let mut t1 = (-b + discrim_sqrt) / (2.0 * a);
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // only keep wall hits within the truncated extents
+            for t in [t0, t1].iter() {
+                let y = ray.origin.1 + t * ray.direction.1;
+                if self.minimum < y && y < self.maximum {
+                    intersections.push(Intersection::new(*t, self));
+                }
+            }
+        }
+
+        // caps can only be hit by rays that aren't parallel to them
+        if self.closed && !fequals(ray.direction.1, 0.0) {
+            for y in [self.minimum, self.maximum].iter() {
+                let t = (y - ray.origin.1) / ray.direction.1;
+                if check_cap(ray, t, 1.0) {
+                    intersections.push(Intersection::new(t, self));
+                }
+            }
+        }
+        intersections
+    }
+
+    fn local_normal_at(&self, point: &Vec4, _hit: &Intersection) -> Vec4 {
+        // points within a unit radius of the axis at the extents
+        // are on the caps, everything else is on the wall
+        let dist = point.0.powi(2) + point.2.powi(2);
+        if dist < 1.0 && point.1 >= self.maximum - EPSILON {
+            vector(0.0, 1.0, 0.0)
+        } else if dist < 1.0 && point.1 <= self.minimum + EPSILON {
+            vector(0.0, -1.0, 0.0)
+        } else {
+            vector(point.0, 0.0, point.2)
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1.0, self.minimum, -1.0), point(1.0, self.maximum, 1.0))
+    }
+}
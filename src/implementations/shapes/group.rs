@@ -0,0 +1,100 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*, bvh::*};
+
+// container for child shapes, its transform applies to all of them
+#[derive(Debug, Clone)]
+pub struct Group {
+    data: ShapeData,
+    // use add_child to keep parent chains intact
+    children: Vec<Box<dyn Shape>>,
+    // hierarchy over the children, see build_bvh
+    bvh: Option<Bvh>
+}
+
+impl Group {
+    pub fn new() -> Group {
+        Group {
+            data: ShapeData::new(),
+            children: Vec::new(),
+            bvh: None
+        }
+    }
+
+    pub fn add_child(&mut self, mut child: Box<dyn Shape>) {
+        child.set_parent_inverse(*self.data.world_inverse());
+        child.set_parent_visibility(self.world_visibility());
+        self.children.push(child);
+        // the old hierarchy doesn't know about the new child
+        self.bvh = None;
+    }
+}
+
+impl Default for Group {
+    fn default() -> Group {
+        Group::new()
+    }
+}
+
+impl Shape for Group {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    fn children_mut_keeping_bvh(&mut self) -> &mut [Box<dyn Shape>] {
+        &mut self.children
+    }
+
+    // Build bounding volume hierarchies for this group and every group
+    // nested inside it. Needs to be called again after children are moved.
+    fn build_bvh(&mut self) {
+        for child in self.children.iter_mut() {
+            child.build_bvh();
+        }
+        self.bvh = Some(Bvh::build(&self.children));
+    }
+
+    fn invalidate_bvh(&mut self) {
+        self.bvh = None;
+        for child in self.children.iter_mut() {
+            child.invalidate_bvh();
+        }
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        // ray is already in group space, children apply their own transforms
+        let mut intersections: Vec<Intersection> = Vec::new();
+        match &self.bvh {
+            Some(bvh) => {
+                for index in bvh.candidates(ray) {
+                    intersections.extend(self.children[index].intersect(ray));
+                }
+            },
+            None => {
+                for child in &self.children {
+                    intersections.extend(child.intersect(ray));
+                }
+            }
+        }
+        sort_intersections(&mut intersections);
+        intersections
+    }
+
+    fn local_normal_at(&self, _point: &Vec4, _hit: &Intersection) -> Vec4 {
+        panic!("Groups have no normal, normal_at should be called on the child that was hit")
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        for child in &self.children {
+            bounds.merge(&child.parent_space_bounds());
+        }
+        bounds
+    }
+}
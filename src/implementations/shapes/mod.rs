@@ -0,0 +1,9 @@
+pub mod sphere;
+pub mod plane;
+pub mod cube;
+pub mod cylinder;
+pub mod cone;
+pub mod triangle;
+pub mod smooth_triangle;
+pub mod group;
+pub mod csg;
@@ -0,0 +1,53 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+
+// infinite xz plane through the origin
+#[derive(Debug, Clone)]
+pub struct Plane {
+    data: ShapeData
+}
+
+impl Plane {
+    pub fn new() -> Plane {
+        Plane {
+            data: ShapeData::new()
+        }
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Plane {
+        Plane::new()
+    }
+}
+
+impl Shape for Plane {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        if ray.direction.1.abs() < EPSILON {
+            return vec![]
+        }
+
+        let t = -ray.origin.1 / ray.direction.1;
+        vec![
+            Intersection::new(t, self)
+        ]
+    }
+
+    fn local_normal_at(&self, _point: &Vec4, _hit: &Intersection) -> Vec4 {
+        vector(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            point(f32::NEG_INFINITY, 0.0, f32::NEG_INFINITY),
+            point(f32::INFINITY, 0.0, f32::INFINITY)
+        )
+    }
+}
@@ -0,0 +1,63 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+use super::triangle::{intersect_triangle, interpolate_texture_coords, triangle_bounds};
+
+// triangle with per-vertex normals interpolated across its face
+#[derive(Debug, Clone)]
+pub struct SmoothTriangle {
+    data: ShapeData,
+    pub p1: Vec4,
+    pub p2: Vec4,
+    pub p3: Vec4,
+    pub n1: Vec4,
+    pub n2: Vec4,
+    pub n3: Vec4,
+    pub e1: Vec4,
+    pub e2: Vec4,
+    // per-vertex texture coordinates, in the same order as the points
+    pub texture_coords: Option<[Vec2; 3]>
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Vec4, p2: Vec4, p3: Vec4, n1: Vec4, n2: Vec4, n3: Vec4) -> SmoothTriangle {
+        SmoothTriangle {
+            data: ShapeData::new(),
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: &p2 - &p1,
+            e2: &p3 - &p1,
+            texture_coords: None
+        }
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        intersect_triangle(self, ray, &self.p1, &self.e1, &self.e2)
+    }
+
+    fn local_normal_at(&self, _point: &Vec4, hit: &Intersection) -> Vec4 {
+        // interpolate vertex normals with the hit's barycentric coordinates
+        &(&(&self.n2 * hit.u) + &(&self.n3 * hit.v)) + &(&self.n1 * (1.0 - hit.u - hit.v))
+    }
+
+    fn texture_coords_at(&self, point: &Vec4) -> Option<Vec2> {
+        let coords = self.texture_coords.as_ref()?;
+        Some(interpolate_texture_coords(&self.world_to_object(point), &self.p1, &self.e1, &self.e2, coords))
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(&self.p1, &self.p2, &self.p3)
+    }
+}
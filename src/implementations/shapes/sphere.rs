@@ -0,0 +1,72 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+
+// unit sphere at the origin
+#[derive(Debug, Clone)]
+pub struct Sphere {
+    data: ShapeData
+}
+
+impl Sphere {
+    pub fn new() -> Sphere {
+        Sphere {
+            data: ShapeData::new()
+        }
+    }
+}
+
+impl Default for Sphere {
+    fn default() -> Sphere {
+        Sphere::new()
+    }
+}
+
+pub fn glass_sphere() -> Sphere {
+    let mut s = Sphere::new();
+    s.material_mut().transparency = 1.0;
+    s.material_mut().refractive_index = 1.5;
+    s
+}
+
+impl Shape for Sphere {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        // calculate ray intersection
+        // solutions via quadratic formula
+        // get discriminant to get number of solutions 
+        // to return
+        let sphere_to_ray = &ray.origin - &point(0.0, 0.0, 0.0);
+        let a = ray.direction.dot(&ray.direction);
+        let b = &ray.direction.dot(&sphere_to_ray) * 2.0;
+        let c = sphere_to_ray.dot(&sphere_to_ray) - 1.0;
+        let discriminant = b.powi(2) as f32 - 4.0 * a * c;
+        let discrim_sqrt = discriminant.sqrt();
+        // TODO: Make more DRY with closures
+        if fequals(discriminant, 0.0) {
+            let i1 = Intersection::new((-b - discrim_sqrt) / (2.0 * a), self);
+            vec![i1]
+        } else {
+            if discriminant > 0.0 {
+                let i1 = Intersection::new((-b - discrim_sqrt) / (2.0 * a), self);
+                let i2 = Intersection::new((-b + discrim_sqrt) / (2.0 * a), self);
+                vec![i1, i2]
+            } else {
+                vec![] as Vec<Intersection>
+            }         
+        }
+    }
+
+    fn local_normal_at(&self, object_point: &Vec4, _hit: &Intersection) -> Vec4 {
+        (object_point - &point(0.0, 0.0, 0.0)).normalize()
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
+    }
+}
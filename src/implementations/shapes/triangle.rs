@@ -0,0 +1,108 @@
+use super::super::{tuples::*, bounds::*, intersection::*, ray::*, shape::*};
+
+// flat triangle, edges and normal are precomputed by new()
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    data: ShapeData,
+    pub p1: Vec4,
+    pub p2: Vec4,
+    pub p3: Vec4,
+    pub e1: Vec4,
+    pub e2: Vec4,
+    pub normal: Vec4,
+    // per-vertex texture coordinates, in the same order as the points
+    pub texture_coords: Option<[Vec2; 3]>
+}
+
+impl Triangle {
+    pub fn new(p1: Vec4, p2: Vec4, p3: Vec4) -> Triangle {
+        let e1 = &p2 - &p1;
+        let e2 = &p3 - &p1;
+        Triangle {
+            data: ShapeData::new(),
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(&e1).normalize(),
+            texture_coords: None
+        }
+    }
+}
+
+// Moller-Trumbore intersection, recording the barycentric u/v of the hit
+pub(crate) fn intersect_triangle<'a>(shape: &'a dyn Shape, ray: &Ray, p1: &Vec4, e1: &Vec4, e2: &Vec4) -> Vec<Intersection<'a>> {
+    let dir_cross_e2 = ray.direction.cross(e2);
+    let det = e1.dot(&dir_cross_e2);
+    // ray is parallel to the triangle's plane
+    if det.abs() < EPSILON {
+        return vec![]
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = &ray.origin - p1;
+    let u = f * p1_to_origin.dot(&dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return vec![]
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(e1);
+    let v = f * ray.direction.dot(&origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return vec![]
+    }
+
+    let t = f * e2.dot(&origin_cross_e1);
+    vec![
+        Intersection::new_with_uv(t, shape, u, v)
+    ]
+}
+
+// Interpolates per-vertex texture coordinates at an object space point.
+// The point is projected onto the triangle's plane, so the barycentric
+// u/v match the ones intersect_triangle records for the same hit.
+pub(crate) fn interpolate_texture_coords(point: &Vec4, p1: &Vec4, e1: &Vec4, e2: &Vec4, coords: &[Vec2; 3]) -> Vec2 {
+    let p1_to_point = point - p1;
+    let (d11, d12, d22) = (e1.dot(e1), e1.dot(e2), e2.dot(e2));
+    let (dp1, dp2) = (p1_to_point.dot(e1), p1_to_point.dot(e2));
+    let denom = d11 * d22 - d12 * d12;
+    let u = (d22 * dp1 - d12 * dp2) / denom;
+    let v = (d11 * dp2 - d12 * dp1) / denom;
+    &(&(&coords[1] * &u) + &(&coords[2] * &v)) + &(&coords[0] * &(1.0 - u - v))
+}
+
+pub(crate) fn triangle_bounds(p1: &Vec4, p2: &Vec4, p3: &Vec4) -> BoundingBox {
+    let mut bounds = BoundingBox::empty();
+    bounds.add_point(p1);
+    bounds.add_point(p2);
+    bounds.add_point(p3);
+    bounds
+}
+
+impl Shape for Triangle {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        intersect_triangle(self, ray, &self.p1, &self.e1, &self.e2)
+    }
+
+    fn local_normal_at(&self, _point: &Vec4, _hit: &Intersection) -> Vec4 {
+        self.normal
+    }
+
+    fn texture_coords_at(&self, point: &Vec4) -> Option<Vec2> {
+        let coords = self.texture_coords.as_ref()?;
+        Some(interpolate_texture_coords(&self.world_to_object(point), &self.p1, &self.e1, &self.e2, coords))
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(&self.p1, &self.p2, &self.p3)
+    }
+}
@@ -1,77 +1,130 @@
-use std::cmp::Ordering;
-
 use crate::implementations::{
+    bvh::*,
     computations::Computations, 
     intersection::*, 
     light::*, 
     matrices::*, 
     ray::*, 
     shape::*, 
+    shapes::sphere::Sphere,
     tuples::*
 };
 
 pub struct World {
-    pub shapes: Vec<Shape>,
-    pub light: Light,
-    pub quick_rendered: bool
+    // only reachable through the accessors below so that changing
+    // shapes always drops a hierarchy built over their old bounds
+    shapes: Vec<Box<dyn Shape>>,
+    // every light's contribution is summed, each with its own shadow test
+    pub lights: Vec<Light>,
+    pub quick_rendered: bool,
+    // built by build_bvh, intersect falls back to testing every
+    // shape after shapes were changed
+    bvh: Option<Bvh>
 }
 
 impl World {
     pub fn new() -> World {
-        let mut s1 = Shape::new(ShapeType::Sphere);
-        s1.material.color = color(0.8, 1.0, 0.6);
-        s1.material.diffuse = 0.7;
-        s1.material.specular = 0.2;
-        let mut s2 = Shape::new(ShapeType::Sphere);
-        s2.transform = scaling(0.5, 0.5, 0.5);
+        let mut s1 = Sphere::new();
+        s1.material_mut().color = color(0.8, 1.0, 0.6);
+        s1.material_mut().diffuse = 0.7;
+        s1.material_mut().specular = 0.2;
+        let mut s2 = Sphere::new();
+        s2.set_transform(scaling(0.5, 0.5, 0.5));
         World {
             shapes: vec![
-                s1,
-                s2
+                Box::new(s1),
+                Box::new(s2)
             ],
-            light: Light {
-                position: point(-10.0, 10.0, -10.0),
-                intensity: color(1.0, 1.0, 1.0)
-            },
-            quick_rendered: false
+            lights: vec![
+                Light::new(point(-10.0, 10.0, -10.0), color(1.0, 1.0, 1.0))
+            ],
+            quick_rendered: false,
+            bvh: None
+        }
+    }
+
+    pub fn shapes(&self) -> &[Box<dyn Shape>] {
+        &self.shapes
+    }
+
+    // shapes, or shapes nested in groups, may be added, removed or moved
+    // through this, so every hierarchy is dropped until build_bvh is
+    // called again
+    pub fn shapes_mut(&mut self) -> &mut Vec<Box<dyn Shape>> {
+        self.bvh = None;
+        for shape in self.shapes.iter_mut() {
+            shape.invalidate_bvh();
         }
+        &mut self.shapes
+    }
+
+    // the other shapes don't move, so their groups keep their hierarchies
+    pub fn add_shape(&mut self, shape: Box<dyn Shape>) {
+        self.bvh = None;
+        self.shapes.push(shape);
+    }
+
+    pub fn has_bvh(&self) -> bool {
+        self.bvh.is_some()
     }
 
     pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
         let mut intersections: Vec<Intersection> = Vec::new();
-        // add any intersections from ray to each shape in world
-        for shape in &self.shapes {
-            for intersection in shape.intersect(&ray) {
-                intersections.push(intersection);
+        match &self.bvh {
+            Some(bvh) => {
+                // only test shapes whose bounds the ray passes through
+                for index in bvh.candidates(ray) {
+                    intersections.extend(self.shapes[index].intersect(ray));
+                }
+            },
+            _ => {
+                // add any intersections from ray to each shape in world
+                for shape in &self.shapes {
+                    for intersection in shape.intersect(&ray) {
+                        intersections.push(intersection);
+                    }
+                }
             }
         }
-        intersections.sort_by(|a, b| 
-            if fequals(a.t, b.t) {
-                Ordering::Equal
-            } else if a.t > b.t {
-                Ordering::Greater
-            } else {
-                Ordering::Less
-            }
-        );
+        sort_intersections(&mut intersections);
         intersections
     }
 
+    // Build bounding volume hierarchies over the world's shapes and
+    // inside every group. Needs to be called again after shapes change,
+    // until then intersect tests every shape.
+    pub fn build_bvh(&mut self) {
+        for shape in self.shapes.iter_mut() {
+            shape.build_bvh();
+        }
+        self.bvh = Some(Bvh::build(&self.shapes));
+    }
+
     pub fn shade_hit(&self, comps: &Computations, remaining: u32) -> Vec4 {
-        let surface = lighting(
-            &comps.object.material, 
-            &comps.object,
-            &self.light, 
-            &comps.point, 
-            &comps.eyev, 
-            &comps.normalv,
-            self.is_shadowed(&comps.over_point)
-        );
+        let mut surface = color(0.0, 0.0, 0.0);
+        for light in self.lights.iter() {
+            // shadow rays leave from over_point, but an area light is sampled
+            // at the same positions lighting uses, which are seeded from point
+            let intensity = if comps.object.world_visibility().receives_shadow {
+                self.intensity_over_samples(light, &light.samples(&comps.point), &comps.over_point)
+            } else {
+                color(1.0, 1.0, 1.0)
+            };
+            surface = &surface + &lighting(
+                comps.object.material(), 
+                comps.object,
+                light, 
+                &comps.point, 
+                &comps.eyev, 
+                &comps.normalv,
+                &intensity
+            );
+        }
 
         let reflected = self.reflected_color(comps, remaining);
         let refracted = self.refracted_color(comps, remaining);
 
-        let material = &comps.object.material;
+        let material = comps.object.material();
         // combine reflectance and transparency using Schlick's formula 
         // if material is both reflective and transparent
         if material.reflective > 0.0 && material.transparency > 0.0 {
@@ -81,14 +134,26 @@ impl World {
         &(&surface + &reflected) + &refracted
     }
 
+    // color seen along a ray from the camera
     pub fn color_at(&self, ray: &Ray, remaining: u32) -> Vec4 {
-        let intersections = self.intersect(&ray);
+        self.trace(ray, remaining, true)
+    }
+
+    // shapes hidden from this kind of ray are dropped before finding the
+    // hit, so they don't affect refractive indices either
+    fn trace(&self, ray: &Ray, remaining: u32, camera_ray: bool) -> Vec4 {
+        let mut intersections = self.intersect(&ray);
+        intersections.retain(|i| if camera_ray {
+            i.object.world_visibility().visible_to_camera
+        } else {
+            i.object.world_visibility().visible_in_reflections
+        });
         if let Some(hit) = hit(intersections.clone()) {
             let hit_clone = hit.clone();
             let comps = hit_clone.prepare_computations(&ray, intersections);
             if self.quick_rendered {
                 let shape = comps.object;
-                let material = &comps.object.material;
+                let material = comps.object.material();
                 let material_color: Vec4;
                 if let Some(pattern) = &material.pattern {
                     material_color = pattern.color_at(shape, &comps.over_point);
@@ -104,24 +169,67 @@ impl World {
         }
     }
 
-    pub fn is_shadowed(&self, p: &Vec4) -> bool {
-        let light_point = self.light.position;
-        let diff = &light_point - p;
-        let distance = diff.mag();
+    // share of the light reaching p averaged over the light's samples,
+    // per channel so shadows through colored glass are tinted
+    pub fn intensity_at(&self, light: &Light, p: &Vec4) -> Vec4 {
+        self.intensity_over_samples(light, &light.samples(p), p)
+    }
+
+    fn intensity_over_samples(&self, light: &Light, samples: &[Vec4], p: &Vec4) -> Vec4 {
+        let mut sum = color(0.0, 0.0, 0.0);
+        for sample in samples.iter() {
+            let (direction, distance) = light.direction_from(sample, p);
+            sum = &sum + &self.transmission(p, &direction, distance);
+        }
+        &sum * (1.0 / samples.len() as f32)
+    }
+
+    // light let through from the light's position, or from a directional
+    // light's direction, to p; black when fully shadowed. Each shape in
+    // the way filters the light once, however many of its surfaces lie
+    // between p and the light
+    pub fn shadow_attenuation(&self, light: &Light, p: &Vec4) -> Vec4 {
+        let (direction, distance) = light.direction_from(&light.position, p);
+        self.transmission(p, &direction, distance)
+    }
+
+    // product of the transparency and color of every shape between p
+    // and distance along direction, taken at the first surface hit
+    fn transmission(&self, p: &Vec4, direction: &Vec4, distance: f32) -> Vec4 {
         let ray = Ray {
             origin: *p,
-            direction: diff.normalize()
+            direction: *direction
         };
-        if let Some(hit) = hit(self.intersect(&ray)) {
-            if hit.t < distance {
-                return true
+        let mut attenuation = color(1.0, 1.0, 1.0);
+        let intersections = self.intersect(&ray);
+        let mut counted: Vec<&ShapeId> = Vec::new();
+        for i in intersections.iter() {
+            if i.t < 0.0 || !i.object.world_visibility().casts_shadow {
+                continue;
+            }
+            if i.t >= distance {
+                break;
+            }
+            if counted.contains(&i.object.id()) {
+                continue;
+            }
+            counted.push(i.object.id());
+
+            let material = i.object.material();
+            if material.transparency <= 0.0 {
+                return color(0.0, 0.0, 0.0)
             }
+            let surface_color = match &material.pattern {
+                Some(pattern) => pattern.color_at(i.object, &ray.position(i.t)),
+                None => material.color
+            };
+            attenuation = &attenuation * &(&surface_color * material.transparency);
         }
-        false
+        attenuation
     }
 
     pub fn reflected_color(&self, comps: &Computations, remaining: u32) -> Vec4 {
-        if remaining <= 0 || fequals(comps.object.material.reflective, 0.0) {
+        if remaining <= 0 || fequals(comps.object.material().reflective, 0.0) {
             return color(0.0, 0.0, 0.0)
         }
 
@@ -129,13 +237,13 @@ impl World {
             origin: comps.over_point,
             direction: comps.reflectv
         };
-        let color = self.color_at(&reflect_ray, remaining - 1);
-        &color * comps.object.material.reflective
+        let color = self.trace(&reflect_ray, remaining - 1, false);
+        &color * comps.object.material().reflective
     }
 
     pub fn refracted_color(&self, comps: &Computations, remaining: u32) -> Vec4 {
         // return black if opaque or refraction call stack cap
-        if comps.object.material.transparency == 0.0 || remaining == 0 {
+        if comps.object.material().transparency == 0.0 || remaining == 0 {
             return color(0.0, 0.0, 0.0)
         }
 
@@ -160,6 +268,6 @@ impl World {
         };
 
         // get color of refracted ray and account for transparency
-        &self.color_at(&refract_ray, remaining - 1) * comps.object.material.transparency
+        &self.trace(&refract_ray, remaining - 1, false) * comps.object.material().transparency
     }
 }
@@ -0,0 +1,303 @@
+use std::{env, fs, path::Path};
+
+use image::{Rgb, RgbImage};
+use ray_tracer::implementations::{
+    material::*,
+    mtl_parser::*,
+    obj_parser::*,
+    ray::*,
+    shape::*,
+    shapes::{smooth_triangle::SmoothTriangle, triangle::Triangle},
+    tuples::*
+};
+
+// corners of a child shape the parser built as a flat triangle
+fn triangle_points(shape: &dyn Shape) -> (Vec4, Vec4, Vec4) {
+    let t = shape.as_any().downcast_ref::<Triangle>().expect("face was not parsed as a Triangle");
+    (t.p1, t.p2, t.p3)
+}
+
+#[test]
+fn ignores_unrecognized_lines() {
+    let gibberish = "There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+in a relative way,
+and came back the previous night.";
+    let parser = parse_obj(gibberish, Path::new("."));
+    assert_eq!(parser.ignored_lines, 5);
+    assert_eq!(parser.default_group.children().len(), 0);
+}
+
+#[test]
+fn vertex_records() {
+    let file = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0";
+    let parser = parse_obj(file, Path::new("."));
+    assert_eq!(parser.ignored_lines, 0);
+    assert_eq!(parser.vertices[0], point(-1.0, 1.0, 0.0));
+    assert_eq!(parser.vertices[1], point(-1.0, 0.5, 0.0));
+    assert_eq!(parser.vertices[2], point(1.0, 0.0, 0.0));
+    assert_eq!(parser.vertices[3], point(1.0, 1.0, 0.0));
+}
+
+#[test]
+fn triangle_faces() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4";
+    let parser = parse_obj(file, Path::new("."));
+    let children = parser.default_group.children();
+    assert_eq!(children.len(), 2);
+    let v = &parser.vertices;
+    assert_eq!(triangle_points(children[0].as_ref()), (v[0], v[1], v[2]));
+    assert_eq!(triangle_points(children[1].as_ref()), (v[0], v[2], v[3]));
+}
+
+#[test]
+fn triangulating_polygons() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5";
+    let parser = parse_obj(file, Path::new("."));
+    let children = parser.default_group.children();
+    assert_eq!(children.len(), 3);
+    let v = &parser.vertices;
+    assert_eq!(triangle_points(children[0].as_ref()), (v[0], v[1], v[2]));
+    assert_eq!(triangle_points(children[1].as_ref()), (v[0], v[2], v[3]));
+    assert_eq!(triangle_points(children[2].as_ref()), (v[0], v[3], v[4]));
+}
+
+#[test]
+fn named_groups() {
+    let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4
+g FirstGroup
+f 2 3 4";
+    let parser = parse_obj(file, Path::new("."));
+    assert_eq!(parser.groups.len(), 2);
+    assert_eq!(parser.group("FirstGroup").unwrap().children().len(), 2);
+    assert_eq!(parser.group("SecondGroup").unwrap().children().len(), 1);
+    assert!(parser.group("ThirdGroup").is_none());
+
+    let group = parser.to_group();
+    assert_eq!(group.children().len(), 2);
+    let r = Ray {
+        origin: point(0.5, 0.5, -2.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    // hits the SecondGroup triangle and the reopened FirstGroup's second one
+    assert_eq!(group.intersect(&r).len(), 2);
+}
+
+#[test]
+fn vertex_normals_and_texture_coords() {
+    let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+vt 0 0
+vt 1 0.5
+
+f 1//3 2//1 3//2
+f 1/1/3 2/2/1 3/1/2
+f -3/-2/-1 -2/-1/-3 -1/-2/-2";
+    let parser = parse_obj(file, Path::new("."));
+    assert_eq!(parser.ignored_lines, 0);
+    assert_eq!(parser.normals[0], vector(-1.0, 0.0, 0.0));
+    assert_eq!(parser.texture_coords[1], Vec2(1.0, 0.5));
+
+    let children = parser.default_group.children();
+    assert_eq!(children.len(), 3);
+    let (v, n) = (&parser.vertices, &parser.normals);
+    for child in children {
+        let t = child.as_any().downcast_ref::<SmoothTriangle>().expect("face was not parsed as a SmoothTriangle");
+        assert_eq!((t.p1, t.p2, t.p3), (v[0], v[1], v[2]));
+        assert_eq!((t.n1, t.n2, t.n3), (n[2], n[0], n[1]));
+    }
+}
+
+#[test]
+fn malformed_faces_are_counted() {
+    let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+v 1 x 0
+f 1 2
+f 1 2 7
+f 1/5 2 3
+f 1 2 3
+usemtl shiny";
+    let parser = parse_obj(file, Path::new("."));
+    assert_eq!(parser.ignored_lines, 5);
+    assert_eq!(parser.default_group.children().len(), 1);
+}
+
+#[test]
+fn mtl_material_fields() {
+    let file = "# shiny red glass
+newmtl red_glass
+Kd 1 0 0
+Ka 0.3 0.3 0.3
+Ks 0.6 0.3 0.3
+Ns 50
+Ni 1.5
+d 0.25
+illum 2
+
+newmtl ghost
+Tr 0.9";
+    let parser = parse_mtl(file, Path::new("."));
+    assert_eq!(parser.materials.len(), 2);
+    // illum has no Material equivalent
+    assert_eq!(parser.ignored_lines, 1);
+
+    let m = &parser.materials["red_glass"];
+    assert_eq!(m.color, color(1.0, 0.0, 0.0));
+    assert!(fequals(m.ambient, 0.3));
+    assert!(fequals(m.specular, 0.4));
+    assert_eq!(m.shininess, 50.0);
+    assert_eq!(m.refractive_index, 1.5);
+    assert_eq!(m.transparency, 0.75);
+    assert!(m.pattern.is_none());
+
+    let m = &parser.materials["ghost"];
+    assert!(fequals(m.transparency, 0.9));
+    assert_eq!(m.color, Material::new().color);
+}
+
+#[test]
+fn obj_with_material_library() {
+    let dir = env::temp_dir().join("ray_tracer_obj_with_material_library");
+    fs::create_dir_all(&dir).unwrap();
+    RgbImage::from_pixel(2, 2, Rgb([0, 255, 0])).save(dir.join("bright green.png")).unwrap();
+    fs::write(dir.join("scene.mtl"), "newmtl red
+Kd 1 0 0
+newmtl textured
+map_Kd bright green.png
+newmtl broken
+map_Kd missing.png
+map_Kd -clamp on bright green.png").unwrap();
+    fs::write(dir.join("scene.obj"), "mtllib scene.mtl missing.mtl
+v 0 1 0
+v -1 0 0
+v 1 0 0
+f 1 2 3
+usemtl red
+f 1 2 3
+usemtl textured
+f 1 2 3
+usemtl unknown
+f 1 2 3").unwrap();
+
+    let parser = parse_obj_file(&dir.join("scene.obj").to_string_lossy()).unwrap();
+    // missing.mtl and the unknown material
+    assert_eq!(parser.ignored_lines, 2);
+    // the missing texture and the one with options
+    assert_eq!(parser.ignored_mtl_lines, 2);
+    assert_eq!(parser.materials.len(), 3);
+
+    let children = parser.default_group.children();
+    assert_eq!(children.len(), 4);
+    assert_eq!(children[0].material().color, color(1.0, 1.0, 1.0));
+    assert_eq!(children[1].material().color, color(1.0, 0.0, 0.0));
+    match &children[2].material().pattern {
+        Some(pattern) => assert_eq!(pattern.color_at(children[2].as_ref(), &point(0.5, 0.5, 0.0)), color(0.0, 1.0, 0.0)),
+        None => panic!("map_Kd did not set a texture pattern")
+    }
+    // usemtl with an unknown name keeps the previous material
+    assert!(children[3].material().pattern.is_some());
+    // the texture can't be found so the material stays untextured
+    assert!(parser.materials["broken"].pattern.is_none());
+}
+
+#[test]
+fn undecodable_texture_is_ignored() {
+    let dir = env::temp_dir().join("ray_tracer_undecodable_texture");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("corrupt.png"), "not an image").unwrap();
+    fs::write(dir.join("scene.mtl"), "newmtl corrupt
+Kd 1 0 0
+map_Kd corrupt.png").unwrap();
+
+    let parser = parse_mtl_file(&dir.join("scene.mtl").to_string_lossy()).unwrap();
+    assert_eq!(parser.ignored_lines, 1);
+    assert_eq!(parser.materials["corrupt"].color, color(1.0, 0.0, 0.0));
+    assert!(parser.materials["corrupt"].pattern.is_none());
+}
+
+#[test]
+fn textures_follow_face_texture_coords() {
+    let dir = env::temp_dir().join("ray_tracer_textures_follow_face_texture_coords");
+    fs::create_dir_all(&dir).unwrap();
+    // red, green on top and blue, white on the bottom
+    let mut image = RgbImage::new(2, 2);
+    image.put_pixel(0, 0, Rgb([255, 0, 0]));
+    image.put_pixel(1, 0, Rgb([0, 255, 0]));
+    image.put_pixel(0, 1, Rgb([0, 0, 255]));
+    image.put_pixel(1, 1, Rgb([255, 255, 255]));
+    image.save(dir.join("quadrants.png")).unwrap();
+    fs::write(dir.join("scene.mtl"), "newmtl quadrants
+map_Kd quadrants.png").unwrap();
+    fs::write(dir.join("scene.obj"), "mtllib scene.mtl
+usemtl quadrants
+v 0 0 0
+v 4 0 0
+v 0 4 0
+vn 0 0 1
+vt 0 0
+vt 1 0
+vt 0 1
+f 1/1 2/2 3/3
+f 1/1/1 2/2/1 3/3/1
+f 3/3 2/2 1/1").unwrap();
+
+    let parser = parse_obj_file(&dir.join("scene.obj").to_string_lossy()).unwrap();
+    assert_eq!(parser.ignored_lines, 0);
+    assert_eq!(parser.ignored_mtl_lines, 0);
+    let children = parser.default_group.children();
+    assert_eq!(children.len(), 3);
+
+    // texture coordinates are interpolated from the corners, so the
+    // same point maps to the same texel however the face is wound
+    let cases = [
+        (point(0.5, 0.5, 0.0), color(0.0, 0.0, 1.0)),
+        (point(3.0, 0.5, 0.0), color(1.0, 1.0, 1.0)),
+        (point(0.5, 3.0, 0.0), color(1.0, 0.0, 0.0)),
+        (point(2.5, 1.0, 0.0), color(1.0, 1.0, 1.0))
+    ];
+    for child in children {
+        let pattern = child.material().pattern.as_ref().expect("map_Kd did not set a texture pattern");
+        for (p, expected) in cases.iter() {
+            assert_eq!(pattern.color_at(child.as_ref(), p), *expected);
+        }
+    }
+    assert_eq!(children[0].texture_coords_at(&point(2.0, 2.0, 0.0)), Some(Vec2(0.5, 0.5)));
+}
+
+#[test]
+fn missing_obj_file_is_an_error() {
+    let path = env::temp_dir().join("ray_tracer_missing_obj_file").join("nothing.obj");
+    assert!(parse_obj_file(&path.to_string_lossy()).is_err());
+}
@@ -1,16 +1,12 @@
-use ray_tracer::implementations::{matrices::*, pattern::*, patterns::checker_pattern::CheckerPattern, patterns::gradient_pattern::GradientPattern, patterns::{ring_pattern::RingPattern, striped_pattern::StripedPattern}, shape::ShapeType, shape::*, tuples::*};
+use ray_tracer::implementations::{matrices::*, noise::Noise, pattern::*, patterns::checker_pattern::CheckerPattern, patterns::gradient_pattern::GradientPattern, patterns::{blend_pattern::BlendPattern, granite_pattern::GranitePattern, marble_pattern::MarblePattern, wood_pattern::WoodPattern, worley_pattern::{DistanceMetric, WorleyOutput, WorleyPattern}, perturbed_pattern::PerturbedPattern, ring_pattern::RingPattern, solid_pattern::SolidPattern, striped_pattern::StripedPattern}, shape::*, shapes::{group::Group, sphere::Sphere}, tuples::*};
 
 static WHITE: Vec4 = Vec4(1.0, 1.0, 1.0, 0.0);
 static BLACK: Vec4 = Vec4(0.0, 0.0, 0.0, 0.0);
 #[test]
 fn test() {
-    let striped_pattern = StripedPattern {
-        a: WHITE, 
-        b: BLACK, 
-        transform: Matrix4x4::ident()
-    };
+    let striped_pattern = StripedPattern::new(WHITE, BLACK);
 
-    let shape = Shape::new(ShapeType::Sphere);
+    let shape = Sphere::new();
 
     assert_eq!(striped_pattern.color_at(&shape, &point(0.0, 0.0, 0.0)), WHITE);
     assert_eq!(striped_pattern.color_at(&shape, &point(0.0, 1.0, 0.0)), WHITE);
@@ -30,41 +26,33 @@ fn test() {
 
 #[test]
 fn stripes_with_object_transformation() {
-    let mut shape = Shape::new(ShapeType::Sphere);
-    shape.transform = scaling(2.0, 2.0, 2.0);
-    shape.material.pattern = Some(Box::new(StripedPattern {
-        a: WHITE, 
-        b: BLACK, 
-        transform: Matrix4x4::ident()
-    }));
-    if let Some(pattern) = &shape.material.pattern {
+    let mut shape = Sphere::new();
+    shape.set_transform(scaling(2.0, 2.0, 2.0));
+    shape.material_mut().pattern = Some(Box::new(StripedPattern::new(WHITE, BLACK)));
+    if let Some(pattern) = &shape.material().pattern {
         let material_color = pattern.color_at(&shape, &point(1.5, 0.0, 0.0));
         assert_eq!(material_color, WHITE);
     } else {
         panic!("Pattern was null when matching shape material");
     }
 
-    let mut shape = Shape::new(ShapeType::Sphere);
-    shape.material.pattern = Some(Box::new(StripedPattern {
-        a: WHITE, 
-        b: BLACK, 
-        transform: scaling(2.0, 2.0, 2.0)
-    }));
-    if let Some(pattern) = &shape.material.pattern {
+    let mut shape = Sphere::new();
+    let mut pattern = StripedPattern::new(WHITE, BLACK);
+    pattern.set_transform(scaling(2.0, 2.0, 2.0));
+    shape.material_mut().pattern = Some(Box::new(pattern));
+    if let Some(pattern) = &shape.material().pattern {
         let material_color = pattern.color_at(&shape, &point(1.5, 0.0, 0.0));
         assert_eq!(material_color, WHITE);
     } else {
         panic!("Pattern was null when matching shape material");
     }
 
-    let mut shape = Shape::new(ShapeType::Sphere);
-    shape.material.pattern = Some(Box::new(StripedPattern {
-        a: WHITE, 
-        b: BLACK, 
-        transform: translation(0.5, 0.0, 0.0)
-    }));
-    shape.transform = scaling(2.0, 2.0, 2.0);
-    if let Some(pattern) = &shape.material.pattern {
+    let mut shape = Sphere::new();
+    let mut pattern = StripedPattern::new(WHITE, BLACK);
+    pattern.set_transform(translation(0.5, 0.0, 0.0));
+    shape.material_mut().pattern = Some(Box::new(pattern));
+    shape.set_transform(scaling(2.0, 2.0, 2.0));
+    if let Some(pattern) = &shape.material().pattern {
         let material_color = pattern.color_at(&shape, &point(2.5, 0.0, 0.0));
         assert_eq!(material_color, WHITE);
     } else {
@@ -74,12 +62,8 @@ fn stripes_with_object_transformation() {
 
 #[test]
 fn gradient_pattern() {
-    let pattern = GradientPattern {
-        a: WHITE,
-        b: BLACK,
-        transform: Matrix4x4::ident()
-    };
-    let shape = Shape::new(ShapeType::Sphere);
+    let pattern = GradientPattern::new(WHITE, BLACK);
+    let shape = Sphere::new();
     assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 0.0)), WHITE);
     assert_eq!(pattern.color_at(&shape, &point(0.25, 0.0, 0.0)), color(0.75, 0.75, 0.75));
     assert_eq!(pattern.color_at(&shape, &point(0.5, 0.0, 0.0)), color(0.5, 0.5, 0.5));
@@ -88,12 +72,8 @@ fn gradient_pattern() {
 
 #[test]
 fn ring_patterns() {
-    let pattern = RingPattern {
-        a: WHITE,
-        b: BLACK,
-        transform: Matrix4x4::ident()
-    };
-    let shape = Shape::new(ShapeType::Sphere);
+    let pattern = RingPattern::new(WHITE, BLACK);
+    let shape = Sphere::new();
     assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 0.0)), WHITE);
     assert_eq!(pattern.color_at(&shape, &point(1.0, 0.0, 0.0)), BLACK);
     assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 1.0)), BLACK);
@@ -103,12 +83,8 @@ fn ring_patterns() {
 
 #[test]
 fn checker_pattern() {
-    let pattern = CheckerPattern {
-        a: WHITE,
-        b: BLACK,
-        transform: Matrix4x4::ident()
-    };
-    let shape = Shape::new(ShapeType::Sphere);
+    let pattern = CheckerPattern::new(WHITE, BLACK);
+    let shape = Sphere::new();
     assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 0.0)), WHITE);
     assert_eq!(pattern.color_at(&shape, &point(0.99, 0.0, 0.0)), WHITE);
     assert_eq!(pattern.color_at(&shape, &point(1.01, 0.0, 0.0)), BLACK);
@@ -118,4 +94,286 @@ fn checker_pattern() {
     
     assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 0.99)), WHITE);
     assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 1.01)), BLACK);
-}
\ No newline at end of file
+}
+#[test]
+fn pattern_on_group_child() {
+    let mut group = Group::new();
+    group.set_transform(scaling(2.0, 2.0, 2.0));
+    let mut shape = Sphere::new();
+    shape.set_transform(translation(0.5, 0.0, 0.0));
+    group.add_child(Box::new(shape));
+
+    let pattern = StripedPattern::new(WHITE, BLACK);
+    // world x of 2.5 is x of 1.25 in group space and 0.75 on the sphere
+    let shape = group.children()[0].as_ref();
+    assert_eq!(pattern.color_at(shape, &point(2.5, 0.0, 0.0)), WHITE);
+    assert_eq!(pattern.color_at(shape, &point(3.5, 0.0, 0.0)), BLACK);
+}
+
+#[test]
+fn pattern_caches_inverse_transform() {
+    let mut pattern = GradientPattern::new(WHITE, BLACK);
+    assert_eq!(pattern.get_inverse_transform(), Matrix4x4::ident());
+    pattern.set_transform(translation(0.5, 0.0, 0.0).scale(2.0, 2.0, 2.0));
+    assert_eq!(pattern.get_transform(), translation(0.5, 0.0, 0.0).scale(2.0, 2.0, 2.0));
+    assert_eq!(pattern.get_inverse_transform(), pattern.get_transform().invert());
+}
+
+#[test]
+fn pattern_equality() {
+    let boxed = |pattern: StripedPattern| -> Box<dyn Pattern> { Box::new(pattern) };
+    assert!(boxed(StripedPattern::new(WHITE, BLACK)) == boxed(StripedPattern::new(WHITE, BLACK)));
+    assert!(boxed(StripedPattern::new(WHITE, BLACK)) != boxed(StripedPattern::new(BLACK, WHITE)));
+
+    // compared by type, fields and transform
+    let gradient: Box<dyn Pattern> = Box::new(GradientPattern::new(WHITE, BLACK));
+    assert!(boxed(StripedPattern::new(WHITE, BLACK)) != gradient);
+    let mut moved = StripedPattern::new(WHITE, BLACK);
+    moved.set_transform(translation(1.0, 0.0, 0.0));
+    assert!(boxed(moved.clone()) != boxed(StripedPattern::new(WHITE, BLACK)));
+    assert!(boxed(moved.clone()) == boxed(moved));
+
+    // nested patterns are compared all the way down
+    let nested = |inner: Vec4| StripedPattern::from_patterns(
+        Box::new(StripedPattern::new(inner, BLACK)),
+        Box::new(SolidPattern::new(WHITE))
+    );
+    assert!(nested(WHITE) == nested(WHITE));
+    assert!(nested(WHITE) != nested(color(1.0, 0.0, 0.0)));
+}
+
+#[test]
+fn nested_patterns() {
+    let red = color(1.0, 0.0, 0.0);
+    let mut inner = StripedPattern::new(WHITE, BLACK);
+    inner.set_transform(scaling(0.5, 1.0, 1.0));
+    let mut pattern = StripedPattern::from_patterns(Box::new(inner), Box::new(SolidPattern::new(red)));
+    pattern.set_transform(scaling(2.0, 1.0, 1.0));
+
+    // the inner stripes are half as wide as the outer stripes' space,
+    // so a quarter as wide as the outer stripes themselves
+    let shape = Sphere::new();
+    assert_eq!(pattern.color_at(&shape, &point(0.25, 0.0, 0.0)), WHITE);
+    assert_eq!(pattern.color_at(&shape, &point(0.75, 0.0, 0.0)), WHITE);
+    assert_eq!(pattern.color_at(&shape, &point(1.25, 0.0, 0.0)), BLACK);
+    assert_eq!(pattern.color_at(&shape, &point(2.5, 0.0, 0.0)), red);
+}
+
+#[test]
+fn blend_pattern() {
+    let shape = Sphere::new();
+    let pattern = BlendPattern::new(Box::new(SolidPattern::new(WHITE)), Box::new(SolidPattern::new(BLACK)));
+    assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 0.0)), color(0.5, 0.5, 0.5));
+    let pattern = BlendPattern::weighted(Box::new(SolidPattern::new(WHITE)), Box::new(SolidPattern::new(BLACK)), 0.25);
+    assert_eq!(pattern.color_at(&shape, &point(0.0, 0.0, 0.0)), color(0.75, 0.75, 0.75));
+
+    // crossing stripes, the second set turned to run along x
+    let mut across = StripedPattern::new(WHITE, BLACK);
+    across.set_transform(rotation_y(90.0));
+    let pattern = BlendPattern::new(Box::new(StripedPattern::new(WHITE, BLACK)), Box::new(across));
+    assert_eq!(pattern.color_at(&shape, &point(0.5, 0.0, -0.5)), WHITE);
+    assert_eq!(pattern.color_at(&shape, &point(0.5, 0.0, 0.5)), color(0.5, 0.5, 0.5));
+    assert_eq!(pattern.color_at(&shape, &point(1.5, 0.0, 0.5)), BLACK);
+}
+
+#[test]
+fn noise_values() {
+    let noise = Noise::new(3);
+    // zero on the integer lattice
+    assert_eq!(noise.noise3(&point(1.0, -2.0, 7.0)), 0.0);
+    assert_eq!(noise.noise4(&point(1.0, -2.0, 7.0), 4.0), 0.0);
+
+    let mut varied = false;
+    for i in 0..200 {
+        let p = point(i as f32 * 0.37, i as f32 * 0.11 - 5.0, i as f32 * 0.23);
+        let n = noise.noise3(&p);
+        assert!((-1.0..=1.0).contains(&n));
+        assert!((-1.0..=1.0).contains(&noise.noise4(&p, i as f32 * 0.05)));
+        assert!((-1.0..=1.0).contains(&noise.fractal(&p, 4)));
+        assert!((0.0..=1.0).contains(&noise.turbulence(&p, 4)));
+        assert!((-1.0..=1.0).contains(&noise.fractal4(&p, i as f32 * 0.05, 4)));
+        assert!((0.0..=1.0).contains(&noise.turbulence4(&p, i as f32 * 0.05, 4)));
+        // same seed, same noise
+        assert_eq!(n, Noise::new(3).noise3(&p));
+        varied = varied || n != Noise::new(4).noise3(&p);
+    }
+    assert!(varied);
+
+    // continuous, nearby points give nearby values
+    let p = point(0.3, 0.6, 0.9);
+    let q = point(0.3001, 0.6, 0.9);
+    assert!((noise.noise3(&p) - noise.noise3(&q)).abs() < 0.001);
+    assert!((noise.noise4(&p, 0.5) - noise.noise4(&q, 0.5)).abs() < 0.001);
+
+    // a single octave is plain noise
+    assert_eq!(noise.fractal(&p, 1), noise.noise3(&p));
+    assert_eq!(noise.fractal4(&p, 0.5, 1), noise.noise4(&p, 0.5));
+    assert_eq!(noise.turbulence4(&p, 0.5, 1), noise.noise4(&p, 0.5).abs());
+    assert!((noise.fractal4(&p, 0.5, 4) - noise.fractal4(&q, 0.5, 4)).abs() < 0.001);
+    assert_ne!(noise.fractal4(&p, 0.5, 4), noise.fractal4(&p, 0.8, 4));
+}
+
+#[test]
+fn perturbed_pattern() {
+    let shape = Sphere::new();
+    let stripes = StripedPattern::new(WHITE, BLACK);
+    let still = PerturbedPattern::new(Box::new(stripes.clone()), 0.0);
+    let perturbed = PerturbedPattern::new(Box::new(stripes.clone()), 0.5);
+
+    let mut moved = 0;
+    for i in 0..100 {
+        let p = point(i as f32 * 0.13, 0.4, i as f32 * 0.07);
+        assert_eq!(still.color_at(&shape, &p), stripes.color_at(&shape, &p));
+        let c = perturbed.color_at(&shape, &p);
+        assert!(c == WHITE || c == BLACK);
+        if c != stripes.color_at(&shape, &p) {
+            moved += 1;
+        }
+    }
+    // the stripe edges wobble, but only a little
+    assert!(moved > 0 && moved < 50);
+}
+
+#[test]
+fn color_ramp() {
+    let red = color(1.0, 0.0, 0.0);
+    let ramp = ColorRamp::new(vec![(1.0, BLACK), (0.0, WHITE), (0.5, red)]);
+    assert_eq!(ramp.color_at(-1.0), WHITE);
+    assert_eq!(ramp.color_at(0.25), color(1.0, 0.5, 0.5));
+    assert_eq!(ramp.color_at(0.5), red);
+    assert_eq!(ramp.color_at(0.75), color(0.5, 0.0, 0.0));
+    assert_eq!(ramp.color_at(2.0), BLACK);
+    assert_eq!(ColorRamp::between(WHITE, BLACK).color_at(0.5), color(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn wood_pattern() {
+    let shape = Sphere::new();
+    let mut wood = WoodPattern::new(ColorRamp::between(WHITE, BLACK));
+    wood.frequency = 1.0;
+    wood.ring_noise = 0.0;
+    // rings are measured out from the y axis
+    assert_eq!(wood.color_at(&shape, &point(0.25, 0.0, 0.0)), color(0.75, 0.75, 0.75));
+    assert_eq!(wood.color_at(&shape, &point(0.0, 5.0, 1.25)), color(0.75, 0.75, 0.75));
+    assert_eq!(wood.color_at(&shape, &point(0.3, 0.0, 0.4)), color(0.5, 0.5, 0.5));
+    wood.set_transform(scaling(2.0, 2.0, 2.0));
+    assert_eq!(wood.color_at(&shape, &point(0.5, 0.0, 0.0)), color(0.75, 0.75, 0.75));
+
+    wood.ring_noise = 0.1;
+    assert_ne!(wood.color_at(&shape, &point(0.55, 0.3, 0.15)), color(0.75, 0.75, 0.75));
+}
+
+#[test]
+fn marble_pattern() {
+    let shape = Sphere::new();
+    let mut marble = MarblePattern::new(ColorRamp::between(WHITE, BLACK));
+    marble.turbulence = 0.0;
+    // straight veins every unit along x
+    assert_eq!(marble.color_at(&shape, &point(0.0, 0.0, 0.0)), BLACK);
+    assert_eq!(marble.color_at(&shape, &point(1.0, 3.0, -2.0)), BLACK);
+    assert_eq!(marble.color_at(&shape, &point(0.5, 0.0, 0.0)), WHITE);
+    marble.frequency = 2.0;
+    assert_eq!(marble.color_at(&shape, &point(0.5, 0.0, 0.0)), BLACK);
+
+    marble.turbulence = 2.0;
+    let mut colors = Vec::new();
+    for i in 0..50 {
+        let c = marble.color_at(&shape, &point(0.5, i as f32 * 0.1, 0.0));
+        assert!((0.0..=1.0).contains(&c.0));
+        colors.push(c);
+    }
+    // the veins wander away from the straight lines
+    assert!(colors.iter().any(|c| *c != WHITE));
+}
+
+#[test]
+fn granite_pattern() {
+    let shape = Sphere::new();
+    let red = color(1.0, 0.0, 0.0);
+    // hard step so every point is either a fleck or the base stone
+    let ramp = ColorRamp::new(vec![(0.0, WHITE), (0.5, WHITE), (0.5001, red), (1.0, red)]);
+    let granite = GranitePattern::new(ramp);
+    let mut flecks = 0;
+    for i in 0..200 {
+        let c = granite.color_at(&shape, &point(i as f32 * 0.031, 0.2, i as f32 * 0.017));
+        assert!(c == WHITE || c == red);
+        if c == red {
+            flecks += 1;
+        }
+    }
+    assert!(flecks > 0 && flecks < 200);
+}
+
+#[test]
+fn worley_pattern() {
+    let shape = Sphere::new();
+    let mut worley = WorleyPattern::new(ColorRamp::between(BLACK, WHITE));
+    for i in 0..100 {
+        let p = point(i as f32 * 0.29 - 10.0, i as f32 * 0.13, i as f32 * -0.17);
+        worley.metric = DistanceMetric::Euclidean;
+        let (f1, f2) = worley.distances(&p);
+        assert!(0.0 <= f1 && f1 <= f2);
+
+        worley.output = WorleyOutput::F1;
+        assert_eq!(worley.color_at(&shape, &p), ColorRamp::between(BLACK, WHITE).color_at(f1));
+        worley.output = WorleyOutput::F2MinusF1;
+        assert_eq!(worley.color_at(&shape, &p), ColorRamp::between(BLACK, WHITE).color_at(f2 - f1));
+
+        // each metric bounds the next for every pair of points
+        worley.metric = DistanceMetric::Chebyshev;
+        let (chebyshev, _) = worley.distances(&p);
+        worley.metric = DistanceMetric::Manhattan;
+        let (manhattan, _) = worley.distances(&p);
+        assert!(chebyshev <= f1 && f1 <= manhattan);
+    }
+
+    // feature points depend on the seed only
+    worley.metric = DistanceMetric::Euclidean;
+    let p = point(0.3, 0.7, -1.2);
+    assert_eq!(WorleyPattern::new(ColorRamp::between(BLACK, WHITE)).distances(&p), worley.distances(&p));
+    worley.seed = 9;
+    assert_ne!(WorleyPattern::new(ColorRamp::between(BLACK, WHITE)).distances(&p), worley.distances(&p));
+}
+
+#[test]
+fn worley_search_is_exact() {
+    let mut worley = WorleyPattern::new(ColorRamp::between(BLACK, WHITE));
+    for metric in [DistanceMetric::Euclidean, DistanceMetric::Manhattan, DistanceMetric::Chebyshev] {
+        worley.metric = metric;
+        for i in 0..1000 {
+            let p = point((i as f32 * 0.6180339).fract() * 20.0, (i as f32 * 0.4142135).fract() * 20.0, (i as f32 * 0.7320508).fract() * 20.0);
+            // every cell close enough to possibly matter
+            let home = (p.0.floor() as i64, p.1.floor() as i64, p.2.floor() as i64);
+            let mut distances: Vec<f32> = Vec::new();
+            for dx in -4..=4 {
+                for dy in -4..=4 {
+                    for dz in -4..=4 {
+                        let feature = worley.feature_point((home.0 + dx, home.1 + dy, home.2 + dz));
+                        distances.push(metric.distance(&p, &feature));
+                    }
+                }
+            }
+            distances.sort_by(|a, b| a.total_cmp(b));
+            assert_eq!(worley.distances(&p), (distances[0], distances[1]));
+        }
+    }
+}
+
+#[test]
+fn worley_cell_colors() {
+    let shape = Sphere::new();
+    let mut worley = WorleyPattern::new(ColorRamp::between(WHITE, WHITE));
+    worley.cell_colors = true;
+    let mut colors: Vec<Vec4> = Vec::new();
+    for i in 0..100 {
+        let c = worley.color_at(&shape, &point(i as f32 * 0.37, 0.5, 0.0));
+        assert!((0.0..=1.0).contains(&c.0) && (0.0..=1.0).contains(&c.1) && (0.0..=1.0).contains(&c.2));
+        if !colors.contains(&c) {
+            colors.push(c);
+        }
+    }
+    // neighbouring cells get different colors
+    assert!(colors.len() > 5);
+    let p = point(2.4, 0.5, 0.0);
+    assert_eq!(worley.color_at(&shape, &p), worley.color_at(&shape, &point(2.4001, 0.5, 0.0)));
+}
@@ -4,6 +4,7 @@ use ray_tracer::implementations::{
     matrices::*,
     material::*,
     shape::*,
+    shapes::{plane::Plane, sphere::{glass_sphere, Sphere}},
     light::*,
     intersection::*,
     world::*
@@ -15,12 +16,12 @@ fn sphere_ray_intersections() {
         origin: point(0.0, 0.0, 0.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let s = Shape::new(ShapeType::Sphere);
+    let s = Sphere::new();
     let intersections = s.intersect(&r);
     assert_ne!(intersections.len(), 0);
     let i1 = &intersections[0];
     let i2 = &intersections[1];
-    assert_eq!(i1.object.origin, s.origin);
+    assert_eq!(i1.object.id(), s.id());
     assert_eq!(i1.t, -1.0);
     assert_eq!(i2.t, 1.0);
 
@@ -32,68 +33,38 @@ fn sphere_ray_intersections() {
     assert_ne!(intersections.len(), 0);
     let i1 = &intersections[0];
     let i2 = &intersections[1];
-    assert_eq!(i1.object.origin, s.origin);
+    assert_eq!(i1.object.id(), s.id());
     assert_eq!(i1.t, -6.0);
     assert_eq!(i2.t, -4.0);
 }
 
 #[test]
 fn test_hit() {
-    let s = Shape::new(ShapeType::Sphere);
-    let i1 = Intersection {
-        object: &s,
-        t: 1.0
-    };
-    let i2 = Intersection {
-        object: &s,
-        t: 2.0
-    };
+    let s = Sphere::new();
+    let i1 = Intersection::new(1.0, &s);
+    let i2 = Intersection::new(2.0, &s);
     let intersections = vec![i1.clone(), i2];
     let i = hit(intersections);
     assert_ne!(i.clone(), None);
     assert_eq!(i.unwrap(), i1);
 
-    let i1 = Intersection {
-        object: &s,
-        t: -1.0
-    };
-    let i2 = Intersection {
-        object: &s,
-        t: 1.0
-    };
+    let i1 = Intersection::new(-1.0, &s);
+    let i2 = Intersection::new(1.0, &s);
     let intersections = vec![i1, i2.clone()];
     let i = hit(intersections);
     assert_ne!(i, None);
     assert_eq!(i.unwrap(), i2);
 
-    let i1 = Intersection {
-        object: &s,
-        t: -2.0
-    };
-    let i2 = Intersection {
-        object: &s,
-        t: -1.0
-    };
+    let i1 = Intersection::new(-2.0, &s);
+    let i2 = Intersection::new(-1.0, &s);
     let intersections = vec![i1, i2];
     let i = hit(intersections);
     assert_eq!(i, None);
 
-    let i1 = Intersection {
-        object: &s,
-        t: 5.0
-    };
-    let i2 = Intersection {
-        object: &s,
-        t: 7.0
-    };
-    let i3 = Intersection {
-        object: &s,
-        t: -3.0
-    };
-    let i4 = Intersection {
-        object: &s,
-        t: 2.0
-    };
+    let i1 = Intersection::new(5.0, &s);
+    let i2 = Intersection::new(7.0, &s);
+    let i3 = Intersection::new(-3.0, &s);
+    let i4 = Intersection::new(2.0, &s);
     let intersections = vec![i1, i2, i3, i4.clone()];
     let i = hit(intersections);
     assert_ne!(i, None);
@@ -123,50 +94,51 @@ fn intersect_transformed_spheres() {
         origin: point(0.0, 0.0, -5.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let mut s = Shape::new(ShapeType::Sphere);
-    s.transform = scaling(2.0, 2.0, 2.0);
+    let mut s = Sphere::new();
+    s.set_transform(scaling(2.0, 2.0, 2.0));
     let intersections = s.intersect(&r);
     assert_eq!(intersections.len(), 2);
     assert_eq!(intersections[0].t, 3.0);
     assert_eq!(intersections[1].t, 7.0);
 
-    s.transform = translation(5.0, 0.0, 0.0);
+    s.set_transform(translation(5.0, 0.0, 0.0));
     let intersections = s.intersect(&r);
     assert_eq!(intersections.len(), 0);
 }
 
 #[test]
 fn sphere_surface_normals() {
-    let s = Shape::new(ShapeType::Sphere);
-    let n = s.normal_at(&point(1.0, 0.0, 0.0));
+    let s = Sphere::new();
+    let n = s.normal_at(&point(1.0, 0.0, 0.0), &Intersection::new(0.0, &s));
     assert_eq!(n, vector(1.0, 0.0, 0.0));
 
-    let n = s.normal_at(&point(0.0, 1.0, 0.0));
+    let n = s.normal_at(&point(0.0, 1.0, 0.0), &Intersection::new(0.0, &s));
     assert_eq!(n, vector(0.0, 1.0, 0.0));
 
-    let n = s.normal_at(&point(0.0, 0.0, 1.0));
+    let n = s.normal_at(&point(0.0, 0.0, 1.0), &Intersection::new(0.0, &s));
     assert_eq!(n, vector(0.0, 0.0, 1.0));
 
     let coord = (3.0 as f32).cbrt() / 3.0;
-    let n = s.normal_at(&point(coord, coord, coord));
+    let n = s.normal_at(&point(coord, coord, coord), &Intersection::new(0.0, &s));
     assert_eq!(n, vector(coord, coord, coord).normalize());
 }
 
 #[test]
 fn transformed_sphere_surface_normals() {
-    let mut s = Shape::new(ShapeType::Sphere);
-    s.transform = translation(0.0, 1.0, 0.0);
-    let n = s.normal_at(&point(0.0, 1.70711, -0.70711));
+    let mut s = Sphere::new();
+    s.set_transform(translation(0.0, 1.0, 0.0));
+    let n = s.normal_at(&point(0.0, 1.70711, -0.70711), &Intersection::new(0.0, &s));
     assert_eq!(n, vector(0.0, 0.70711, -0.70711));
 
-    s.transform = &scaling(1.0, 0.5, 1.0)
-        * &rotation_z(36.0);
+    s.set_transform(&scaling(1.0, 0.5, 1.0)
+        * &rotation_z(36.0));
     let n = s.normal_at(
         &point(
             0.0, 
             (2.0 as f32).sqrt() / 2.0, 
             -(2.0 as f32).sqrt() / 2.0
-        )
+        ),
+        &Intersection::new(0.0, &s)
     );
     assert_eq!(n, vector(0.0, 0.97014, -0.24254));
 }
@@ -190,52 +162,40 @@ fn lighting_test() {
     let m = Material::new();
     let position = point(0.0, 0.0, 0.0);
 
-    let shape = Shape::new(ShapeType::Sphere);
+    let shape = Sphere::new();
 
     // light and eye opposite to normal
     let eyev = vector(0.0, 0.0, -1.0);
     let normalv = vector(0.0, 0.0, -1.0);
-    let light = Light {
-        position: point(0.0, 0.0, -10.0),
-        intensity: color(1.0, 1.0, 1.0)
-    };
-    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, false);
+    let light = Light::new(point(0.0, 0.0, -10.0), color(1.0, 1.0, 1.0));
+    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, &color(1.0, 1.0, 1.0));
     assert_eq!(result, color(1.9, 1.9, 1.9));
 
     // light opposite to normal, eye at 45 degrees
     let eyev = vector(0.0, (2.0 as f32).sqrt() / 2.0, -(2.0 as f32).sqrt() / 2.0);
     let normalv = vector(0.0, 0.0, -1.0);
-    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, false);
+    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, &color(1.0, 1.0, 1.0));
     assert_eq!(result, color(1.0, 1.0, 1.0));
 
     // eye directly opposite to surface normal, light at 45 degrees
     let eyev = vector(0.0, 0.0, -1.0);
     let normalv = vector(0.0, 0.0, -1.0);
-    let light = Light {
-        position: vector(0.0, 10.0, -10.0),
-        intensity: color(1.0, 1.0, 1.0)
-    };
-    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, false);
+    let light = Light::new(vector(0.0, 10.0, -10.0), color(1.0, 1.0, 1.0));
+    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, &color(1.0, 1.0, 1.0));
     assert_eq!(result, color(0.73481, 0.73481, 0.73481));
 
     // eye and sun at opposing 45 degrees
     let eyev = vector(0.0, -(2.0 as f32).sqrt() / 2.0, -(2.0 as f32).sqrt() / 2.0);
     let normalv = vector(0.0, 0.0, -1.0);
-    let light = Light {
-        position: point(0.0, 10.0, -10.0),
-        intensity: color(1.0, 1.0, 1.0)
-    };
-    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, false);
+    let light = Light::new(point(0.0, 10.0, -10.0), color(1.0, 1.0, 1.0));
+    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, &color(1.0, 1.0, 1.0));
     assert_eq!(result, color(1.6363853, 1.6363853, 1.6363853));
 
     // light behind surface
     let eyev = vector(0.0, 0.0, -1.0);
     let normalv = vector(0.0, 0.0, -1.0);
-    let light = Light {
-        position: point(0.0, 0.0, 10.0),
-        intensity: color(1.0, 1.0, 1.0)
-    };
-    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, false);
+    let light = Light::new(point(0.0, 0.0, 10.0), color(1.0, 1.0, 1.0));
+    let result = lighting(&m, &shape, &light, &position, &eyev, &normalv, &color(1.0, 1.0, 1.0));
     assert_eq!(result, color(0.1, 0.1, 0.1));
 }
 
@@ -245,11 +205,8 @@ fn prepare_computations() {
         origin: point(0.0, 0.0, -5.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let shape = Shape::new(ShapeType::Sphere);
-    let i = Intersection {
-        object: &shape,
-        t: 4.0
-    };
+    let shape = Sphere::new();
+    let i = Intersection::new(4.0, &shape);
     let comps = i.prepare_computations(&r, vec![i]);
     assert_eq!(comps.object, i.object);
     assert_eq!(comps.point, point(0.0, 0.0, -1.0));
@@ -262,10 +219,7 @@ fn prepare_computations() {
         origin: point(0.0, 0.0, 0.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let i = Intersection {
-        object: &shape,
-        t: 1.0
-    };
+    let i = Intersection::new(1.0, &shape);
     let comps = i.prepare_computations(&r, vec![i]);
     assert_eq!(comps.point, point(0.0, 0.0, 1.0));
     assert_eq!(comps.eyev, vector(0.0, 0.0, -1.0));
@@ -275,15 +229,12 @@ fn prepare_computations() {
 
 #[test]
 fn reflect_vector_test() {
-    let shape = Shape::new(ShapeType::Plane);
+    let shape = Plane::new();
     let r = Ray {
         origin: point(0.0, 1.0, -1.0),
         direction: vector(0.0, -(2.0 as f32).sqrt() / 2.0, (2.0 as f32).sqrt() / 2.0)
     };
-    let i = Intersection {
-        t: (2.0 as f32).sqrt(),
-        object: &shape
-    };
+    let i = Intersection::new((2.0 as f32).sqrt(), &shape);
     let comps = i.prepare_computations(&r, vec![i]);
     assert_eq!(&comps.reflectv, &vector(0.0, (2.0 as f32).sqrt() / 2.0, (2.0 as f32).sqrt() / 2.0));
 }
@@ -291,18 +242,15 @@ fn reflect_vector_test() {
 #[test]
 fn reflective_surface() {
     let mut w = World::new();
-    let mut shape = Shape::new(ShapeType::Plane);
-    shape.material.reflective = 0.5;
-    shape.transform = translation(0.0, -1.0, 0.0);
-    w.shapes.push(shape.clone());
+    let mut shape = Plane::new();
+    shape.material_mut().reflective = 0.5;
+    shape.set_transform(translation(0.0, -1.0, 0.0));
+    w.add_shape(Box::new(shape.clone()));
     let r = Ray {
         origin: point(0.0, 0.0, -3.0),
         direction: vector(0.0, -(2.0 as f32).sqrt() / 2.0, (2.0 as f32).sqrt() / 2.0)
     };
-    let i = Intersection {
-        t: (2.0 as f32).sqrt(),
-        object: &shape
-    };
+    let i = Intersection::new((2.0 as f32).sqrt(), &shape);
     let comps = i.prepare_computations(&r, vec![i]);
     let c = w.reflected_color(&comps, 1);
     assert_eq!(&c, &color(0.19205, 0.24006, 0.14404));
@@ -310,16 +258,16 @@ fn reflective_surface() {
 
 fn test_refraction_n1_n2_helper(index: usize, n1: f32, n2: f32) {
     let mut a = glass_sphere();
-    a.transform = scaling(2.0, 2.0, 2.0);
-    a.material.refractive_index = 1.5;
+    a.set_transform(scaling(2.0, 2.0, 2.0));
+    a.material_mut().refractive_index = 1.5;
 
     let mut b = glass_sphere();
-    b.transform = translation(0.0, 0.0, -0.25);
-    b.material.refractive_index = 2.0;
+    b.set_transform(translation(0.0, 0.0, -0.25));
+    b.material_mut().refractive_index = 2.0;
     
     let mut c = glass_sphere();
-    c.transform = translation(0.0, 0.0, 0.25);
-    c.material.refractive_index = 2.5;
+    c.set_transform(translation(0.0, 0.0, 0.25));
+    c.material_mut().refractive_index = 2.5;
 
     let r = Ray {
         origin: point(0.0, 0.0, -4.0),
@@ -327,30 +275,12 @@ fn test_refraction_n1_n2_helper(index: usize, n1: f32, n2: f32) {
     };
 
     let intersections = vec![
-        Intersection {
-            t: 2.0,
-            object: &a
-        },
-        Intersection {
-            t: 2.75,
-            object: &b
-        },
-        Intersection {
-            t: 3.25,
-            object: &c
-        },
-        Intersection {
-            t: 4.75,
-            object: &b
-        },
-        Intersection {
-            t: 5.25,
-            object: &c
-        },
-        Intersection {
-            t: 6.0,
-            object: &a
-        }        
+        Intersection::new(2.0, &a),
+        Intersection::new(2.75, &b),
+        Intersection::new(3.25, &c),
+        Intersection::new(4.75, &b),
+        Intersection::new(5.25, &c),
+        Intersection::new(6.0, &a)        
     ];
 
     let intersections_head_clone = intersections[index].clone();
@@ -377,12 +307,9 @@ fn under_point_test() {
         direction: vector(0.0, 0.0, 1.0)
     };
     let mut shape = glass_sphere();
-    shape.transform = translation(0.0, 0.0, 1.0);
+    shape.set_transform(translation(0.0, 0.0, 1.0));
 
-    let i = Intersection {
-        t: 5.0,
-        object: &shape
-    };
+    let i = Intersection::new(5.0, &shape);
     let comps = i.prepare_computations(&r, vec![i]);
     assert!(comps.under_point.2 > EPSILON / 2.0);
     println!("Point: {:#?} Under-point: {:#?}", comps.point, comps.under_point);
@@ -392,20 +319,14 @@ fn under_point_test() {
 #[test]
 fn refraction_when_transparent() {
     let w = World::new();
-    let s = w.shapes.first().unwrap();
+    let s = w.shapes().first().unwrap();
     let r = Ray {
         origin: point(0.0, 0.0, -5.0),
         direction: vector(0.0, 0.0, 1.0)
     };
     let intersections = vec![
-        Intersection {
-            t: 4.0,
-            object: s
-        },
-        Intersection {
-            t: 6.0,
-            object: s
-        }
+        Intersection::new(4.0, s.as_ref()),
+        Intersection::new(6.0, s.as_ref())
     ];
     let comps = intersections.first().unwrap().prepare_computations(&r, intersections.clone());
     let c = w.refracted_color(&comps, 5);
@@ -415,22 +336,16 @@ fn refraction_when_transparent() {
 #[test]
 fn total_internal_refraction() {
     let w = World::new();
-    let mut s = w.shapes.first().unwrap().clone();
-    s.material.transparency = 1.0;
-    s.material.refractive_index = 1.5;
+    let mut s = w.shapes().first().unwrap().clone();
+    s.material_mut().transparency = 1.0;
+    s.material_mut().refractive_index = 1.5;
     let r = Ray {
         origin: point(0.0, 0.0, (2.0 as f32).sqrt() / 2.0),
         direction: vector(0.0, 1.0, 0.0)
     };
     let intersections = vec![
-        Intersection {
-            t: -(2.0 as f32).sqrt() / 2.0,
-            object: &s
-        },
-        Intersection {
-            t: (2.0 as f32).sqrt() / 2.0,
-            object: &s
-        }
+        Intersection::new(-(2.0 as f32).sqrt() / 2.0, s.as_ref()),
+        Intersection::new((2.0 as f32).sqrt() / 2.0, s.as_ref())
     ];
     let intersections_head = intersections.clone()[1];
     let comps = intersections_head.prepare_computations(&r, intersections);
@@ -440,12 +355,12 @@ fn total_internal_refraction() {
 #[test]
 fn refracted_color() {
     let w = World::new();
-    let mut a = w.shapes[0].clone();
-    a.material.ambient = 1.0;
+    let mut a = w.shapes()[0].clone();
+    a.material_mut().ambient = 1.0;
     
-    let mut b = w.shapes[1].clone();
-    b.material.transparency = 1.0;
-    b.material.refractive_index = 1.5;
+    let mut b = w.shapes()[1].clone();
+    b.material_mut().transparency = 1.0;
+    b.material_mut().refractive_index = 1.5;
 
     let r = Ray {
         origin: point(0.0, 0.0, 0.1),
@@ -453,22 +368,10 @@ fn refracted_color() {
     };
 
     let intersections = vec![
-        Intersection {
-            t: -0.9899,
-            object: &a
-        },
-        Intersection {
-            t: -0.4899,
-            object: &b
-        },
-        Intersection {
-            t: 0.4899,
-            object: &b
-        },
-        Intersection {
-            t: 0.9899,
-            object: &a
-        }
+        Intersection::new(-0.9899, a.as_ref()),
+        Intersection::new(-0.4899, b.as_ref()),
+        Intersection::new(0.4899, b.as_ref()),
+        Intersection::new(0.9899, a.as_ref())
     ];
     let i = intersections[2].clone();
     let comps = i.prepare_computations(&r, intersections);
@@ -0,0 +1,771 @@
+use ray_tracer::implementations::{
+    bounds::*,
+    intersection::*,
+    matrices::*,
+    ray::*,
+    tuples::*,
+    world::*,
+    shape::*,
+    shapes::{
+        cone::Cone,
+        csg::{Csg, CsgOperation},
+        cube::Cube,
+        cylinder::Cylinder,
+        group::Group,
+        plane::Plane,
+        smooth_triangle::SmoothTriangle,
+        sphere::{glass_sphere, Sphere},
+        triangle::Triangle
+    }
+};
+
+fn test_cube_intersection_helper(origin: Vec4, direction: Vec4, t1: f32, t2: f32) {
+    let c = Cube::new();
+    let r = Ray {
+        origin,
+        direction
+    };
+    let intersections = c.intersect(&r);
+    assert_eq!(intersections.len(), 2);
+    assert_eq!(intersections[0].t, t1);
+    assert_eq!(intersections[1].t, t2);
+}
+
+#[test]
+fn ray_intersects_cube() {
+    test_cube_intersection_helper(point(5.0, 0.5, 0.0), vector(-1.0, 0.0, 0.0), 4.0, 6.0);
+    test_cube_intersection_helper(point(-5.0, 0.5, 0.0), vector(1.0, 0.0, 0.0), 4.0, 6.0);
+    test_cube_intersection_helper(point(0.5, 5.0, 0.0), vector(0.0, -1.0, 0.0), 4.0, 6.0);
+    test_cube_intersection_helper(point(0.5, -5.0, 0.0), vector(0.0, 1.0, 0.0), 4.0, 6.0);
+    test_cube_intersection_helper(point(0.5, 0.0, 5.0), vector(0.0, 0.0, -1.0), 4.0, 6.0);
+    test_cube_intersection_helper(point(0.5, 0.0, -5.0), vector(0.0, 0.0, 1.0), 4.0, 6.0);
+    // ray originating inside cube
+    test_cube_intersection_helper(point(0.0, 0.5, 0.0), vector(0.0, 0.0, 1.0), -1.0, 1.0);
+}
+
+#[test]
+fn ray_misses_cube() {
+    let c = Cube::new();
+    let rays = vec![
+        (point(-2.0, 0.0, 0.0), vector(0.2673, 0.5345, 0.8018)),
+        (point(0.0, -2.0, 0.0), vector(0.8018, 0.2673, 0.5345)),
+        (point(0.0, 0.0, -2.0), vector(0.5345, 0.8018, 0.2673)),
+        (point(2.0, 0.0, 2.0), vector(0.0, 0.0, -1.0)),
+        (point(0.0, 2.0, 2.0), vector(0.0, -1.0, 0.0)),
+        (point(2.0, 2.0, 0.0), vector(-1.0, 0.0, 0.0))
+    ];
+    for (origin, direction) in rays {
+        let r = Ray {
+            origin,
+            direction
+        };
+        assert_eq!(c.intersect(&r).len(), 0);
+    }
+}
+
+#[test]
+fn cube_surface_normals() {
+    let c = Cube::new();
+    assert_eq!(c.normal_at(&point(1.0, 0.5, -0.8), &Intersection::new(0.0, &c)), vector(1.0, 0.0, 0.0));
+    assert_eq!(c.normal_at(&point(-1.0, -0.2, 0.9), &Intersection::new(0.0, &c)), vector(-1.0, 0.0, 0.0));
+    assert_eq!(c.normal_at(&point(-0.4, 1.0, -0.1), &Intersection::new(0.0, &c)), vector(0.0, 1.0, 0.0));
+    assert_eq!(c.normal_at(&point(0.3, -1.0, -0.7), &Intersection::new(0.0, &c)), vector(0.0, -1.0, 0.0));
+    assert_eq!(c.normal_at(&point(-0.6, 0.3, 1.0), &Intersection::new(0.0, &c)), vector(0.0, 0.0, 1.0));
+    assert_eq!(c.normal_at(&point(0.4, 0.4, -1.0), &Intersection::new(0.0, &c)), vector(0.0, 0.0, -1.0));
+    // corners resolve to the x face
+    assert_eq!(c.normal_at(&point(1.0, 1.0, 1.0), &Intersection::new(0.0, &c)), vector(1.0, 0.0, 0.0));
+    assert_eq!(c.normal_at(&point(-1.0, -1.0, -1.0), &Intersection::new(0.0, &c)), vector(-1.0, 0.0, 0.0));
+}
+
+#[test]
+fn ray_misses_cylinder() {
+    let cyl = Cylinder::new(f32::NEG_INFINITY, f32::INFINITY, false);
+    let rays = vec![
+        (point(1.0, 0.0, 0.0), vector(0.0, 1.0, 0.0)),
+        (point(0.0, 0.0, 0.0), vector(0.0, 1.0, 0.0)),
+        (point(0.0, 0.0, -5.0), vector(1.0, 1.0, 1.0))
+    ];
+    for (origin, direction) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        assert_eq!(cyl.intersect(&r).len(), 0);
+    }
+}
+
+#[test]
+fn ray_hits_cylinder() {
+    let cyl = Cylinder::new(f32::NEG_INFINITY, f32::INFINITY, false);
+    let rays = vec![
+        (point(1.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), 5.0, 5.0),
+        (point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), 4.0, 6.0),
+        (point(0.5, 0.0, -5.0), vector(0.1, 1.0, 1.0), 6.80798, 7.08872)
+    ];
+    for (origin, direction, t0, t1) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        let intersections = cyl.intersect(&r);
+        assert_eq!(intersections.len(), 2);
+        // f32 round-off through normalize() exceeds EPSILON here
+        assert!((intersections[0].t - t0).abs() < 0.0001);
+        assert!((intersections[1].t - t1).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn cylinder_surface_normals() {
+    let cyl = Cylinder::new(f32::NEG_INFINITY, f32::INFINITY, false);
+    assert_eq!(cyl.normal_at(&point(1.0, 0.0, 0.0), &Intersection::new(0.0, &cyl)), vector(1.0, 0.0, 0.0));
+    assert_eq!(cyl.normal_at(&point(0.0, 5.0, -1.0), &Intersection::new(0.0, &cyl)), vector(0.0, 0.0, -1.0));
+    assert_eq!(cyl.normal_at(&point(0.0, -2.0, 1.0), &Intersection::new(0.0, &cyl)), vector(0.0, 0.0, 1.0));
+    assert_eq!(cyl.normal_at(&point(-1.0, 1.0, 0.0), &Intersection::new(0.0, &cyl)), vector(-1.0, 0.0, 0.0));
+}
+
+#[test]
+fn truncated_cylinder() {
+    let cyl = Cylinder::new(1.0, 2.0, false);
+    let rays = vec![
+        (point(0.0, 1.5, 0.0), vector(0.1, 1.0, 0.0), 0),
+        (point(0.0, 3.0, -5.0), vector(0.0, 0.0, 1.0), 0),
+        (point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), 0),
+        (point(0.0, 2.0, -5.0), vector(0.0, 0.0, 1.0), 0),
+        (point(0.0, 1.0, -5.0), vector(0.0, 0.0, 1.0), 0),
+        (point(0.0, 1.5, -2.0), vector(0.0, 0.0, 1.0), 2)
+    ];
+    for (origin, direction, count) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        assert_eq!(cyl.intersect(&r).len(), count);
+    }
+}
+
+#[test]
+fn capped_cylinder() {
+    let cyl = Cylinder::new(1.0, 2.0, true);
+    let rays = vec![
+        (point(0.0, 3.0, 0.0), vector(0.0, -1.0, 0.0), 2),
+        (point(0.0, 3.0, -2.0), vector(0.0, -1.0, 2.0), 2),
+        (point(0.0, 4.0, -2.0), vector(0.0, -1.0, 1.0), 2),
+        (point(0.0, 0.0, -2.0), vector(0.0, 1.0, 2.0), 2),
+        (point(0.0, -1.0, -2.0), vector(0.0, 1.0, 1.0), 2)
+    ];
+    for (origin, direction, count) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        assert_eq!(cyl.intersect(&r).len(), count);
+    }
+}
+
+#[test]
+fn capped_cylinder_normals() {
+    let cyl = Cylinder::new(1.0, 2.0, true);
+    assert_eq!(cyl.normal_at(&point(0.0, 1.0, 0.0), &Intersection::new(0.0, &cyl)), vector(0.0, -1.0, 0.0));
+    assert_eq!(cyl.normal_at(&point(0.5, 1.0, 0.0), &Intersection::new(0.0, &cyl)), vector(0.0, -1.0, 0.0));
+    assert_eq!(cyl.normal_at(&point(0.0, 1.0, 0.5), &Intersection::new(0.0, &cyl)), vector(0.0, -1.0, 0.0));
+    assert_eq!(cyl.normal_at(&point(0.0, 2.0, 0.0), &Intersection::new(0.0, &cyl)), vector(0.0, 1.0, 0.0));
+    assert_eq!(cyl.normal_at(&point(0.5, 2.0, 0.0), &Intersection::new(0.0, &cyl)), vector(0.0, 1.0, 0.0));
+    assert_eq!(cyl.normal_at(&point(0.0, 2.0, 0.5), &Intersection::new(0.0, &cyl)), vector(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn ray_hits_cone() {
+    let shape = Cone::new(f32::NEG_INFINITY, f32::INFINITY, false);
+    let rays = vec![
+        (point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), 5.0, 5.0),
+        (point(0.0, 0.0, -5.0), vector(1.0, 1.0, 1.0), 8.66025, 8.66025),
+        (point(1.0, 1.0, -5.0), vector(-0.5, -1.0, 1.0), 4.55006, 49.44994)
+    ];
+    for (origin, direction, t0, t1) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        let intersections = shape.intersect(&r);
+        assert_eq!(intersections.len(), 2);
+        // f32 round-off grows with t, so compare relative to the expected distance
+        assert!((intersections[0].t - t0).abs() < t0 * 0.0001);
+        assert!((intersections[1].t - t1).abs() < t1 * 0.0001);
+    }
+}
+
+#[test]
+fn ray_parallel_to_cone_half() {
+    let shape = Cone::new(f32::NEG_INFINITY, f32::INFINITY, false);
+    let r = Ray {
+        origin: point(0.0, 0.0, -1.0),
+        direction: vector(0.0, 1.0, 1.0).normalize()
+    };
+    let intersections = shape.intersect(&r);
+    assert_eq!(intersections.len(), 1);
+    assert!(fequals(intersections[0].t, 0.35355));
+}
+
+#[test]
+fn capped_cone() {
+    let shape = Cone::new(-0.5, 0.5, true);
+    let rays = vec![
+        (point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0), 0),
+        (point(0.0, 0.0, -0.25), vector(0.0, 1.0, 1.0), 2),
+        (point(0.0, 0.0, -0.25), vector(0.0, 1.0, 0.0), 4)
+    ];
+    for (origin, direction, count) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        assert_eq!(shape.intersect(&r).len(), count);
+    }
+}
+
+#[test]
+fn cone_surface_normals() {
+    let shape = Cone::new(f32::NEG_INFINITY, f32::INFINITY, false);
+    assert_eq!(shape.normal_at(&point(1.0, 1.0, 1.0), &Intersection::new(0.0, &shape)), vector(1.0, -(2.0 as f32).sqrt(), 1.0).normalize());
+    assert_eq!(shape.normal_at(&point(-1.0, -1.0, 0.0), &Intersection::new(0.0, &shape)), vector(-1.0, 1.0, 0.0).normalize());
+    // apex of a double cone points out of the upper nappe's tip
+    assert_eq!(shape.normal_at(&point(0.0, 0.0, 0.0), &Intersection::new(0.0, &shape)), vector(0.0, -1.0, 0.0));
+
+    // apex of a lower-nappe-only cone points up out of the tip
+    let shape = Cone::new(-1.0, 0.0, true);
+    assert_eq!(shape.normal_at(&point(0.0, 0.0, 0.0), &Intersection::new(0.0, &shape)), vector(0.0, 1.0, 0.0));
+    assert_eq!(shape.normal_at(&point(0.5, -1.0, 0.0), &Intersection::new(0.0, &shape)), vector(0.0, -1.0, 0.0));
+}
+
+fn default_triangle() -> Triangle {
+    Triangle::new(point(0.0, 1.0, 0.0), point(-1.0, 0.0, 0.0), point(1.0, 0.0, 0.0))
+}
+
+#[test]
+fn construct_triangle() {
+    let t = default_triangle();
+    assert_eq!(t.e1, vector(-1.0, -1.0, 0.0));
+    assert_eq!(t.e2, vector(1.0, -1.0, 0.0));
+    assert_eq!(t.normal, vector(0.0, 0.0, -1.0));
+
+    // normal is constant across the face
+    let i = Intersection::new(0.0, &t);
+    assert_eq!(t.normal_at(&point(0.0, 0.5, 0.0), &i), vector(0.0, 0.0, -1.0));
+    assert_eq!(t.normal_at(&point(-0.5, 0.75, 0.0), &i), vector(0.0, 0.0, -1.0));
+    assert_eq!(t.normal_at(&point(0.5, 0.25, 0.0), &i), vector(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn ray_misses_triangle() {
+    let t = default_triangle();
+    let rays = vec![
+        // parallel to the triangle
+        (point(0.0, -1.0, -2.0), vector(0.0, 1.0, 0.0)),
+        // past each edge
+        (point(1.0, 1.0, -2.0), vector(0.0, 0.0, 1.0)),
+        (point(-1.0, 1.0, -2.0), vector(0.0, 0.0, 1.0)),
+        (point(0.0, -1.0, -2.0), vector(0.0, 0.0, 1.0))
+    ];
+    for (origin, direction) in rays {
+        let r = Ray {
+            origin,
+            direction
+        };
+        assert_eq!(t.intersect(&r).len(), 0);
+    }
+}
+
+#[test]
+fn ray_hits_triangle() {
+    let t = default_triangle();
+    let r = Ray {
+        origin: point(0.0, 0.5, -2.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let intersections = t.intersect(&r);
+    assert_eq!(intersections.len(), 1);
+    assert_eq!(intersections[0].t, 2.0);
+}
+
+fn default_smooth_triangle() -> SmoothTriangle {
+    SmoothTriangle::new(
+        point(0.0, 1.0, 0.0),
+        point(-1.0, 0.0, 0.0),
+        point(1.0, 0.0, 0.0),
+        vector(0.0, 1.0, 0.0),
+        vector(-1.0, 0.0, 0.0),
+        vector(1.0, 0.0, 0.0)
+    )
+}
+
+#[test]
+fn smooth_triangle_stores_uv() {
+    let t = default_smooth_triangle();
+    let r = Ray {
+        origin: point(-0.2, 0.3, -2.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let intersections = t.intersect(&r);
+    assert_eq!(intersections.len(), 1);
+    assert!(fequals(intersections[0].u, 0.45));
+    assert!(fequals(intersections[0].v, 0.25));
+}
+
+#[test]
+fn smooth_triangle_interpolates_normal() {
+    let t = default_smooth_triangle();
+    let i = Intersection::new_with_uv(1.0, &t, 0.45, 0.25);
+    let n = t.normal_at(&point(0.0, 0.0, 0.0), &i);
+    assert_eq!(n, vector(-0.5547, 0.83205, 0.0));
+
+    // prepare_computations passes the hit through to normal_at
+    let r = Ray {
+        origin: point(-0.2, 0.3, -2.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let comps = i.prepare_computations(&r, vec![i]);
+    assert_eq!(comps.normalv, vector(-0.5547, 0.83205, 0.0));
+}
+
+#[test]
+fn intersect_empty_group() {
+    let g = Group::new();
+    let r = Ray {
+        origin: point(0.0, 0.0, 0.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    assert_eq!(g.intersect(&r).len(), 0);
+}
+
+#[test]
+fn intersect_group() {
+    let mut g = Group::new();
+    let s1 = Sphere::new();
+    let mut s2 = Sphere::new();
+    s2.set_transform(translation(0.0, 0.0, -3.0));
+    let mut s3 = Sphere::new();
+    s3.set_transform(translation(5.0, 0.0, 0.0));
+    g.add_child(Box::new(s1));
+    g.add_child(Box::new(s2));
+    g.add_child(Box::new(s3));
+    assert_eq!(g.children().len(), 3);
+
+    let r = Ray {
+        origin: point(0.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let intersections = g.intersect(&r);
+    assert_eq!(intersections.len(), 4);
+    assert_eq!(intersections[0].object, g.children()[1].as_ref());
+    assert_eq!(intersections[1].object, g.children()[1].as_ref());
+    assert_eq!(intersections[2].object, g.children()[0].as_ref());
+    assert_eq!(intersections[3].object, g.children()[0].as_ref());
+}
+
+#[test]
+fn intersect_transformed_group() {
+    let mut g = Group::new();
+    g.set_transform(scaling(2.0, 2.0, 2.0));
+    let mut s = Sphere::new();
+    s.set_transform(translation(5.0, 0.0, 0.0));
+    g.add_child(Box::new(s));
+
+    let r = Ray {
+        origin: point(10.0, 0.0, -10.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    assert_eq!(g.intersect(&r).len(), 2);
+}
+
+// sphere nested two groups deep, the inner group is filled before
+// being added so its child has to pick up the outer transform too
+fn nested_group() -> Group {
+    let mut g1 = Group::new();
+    g1.set_transform(rotation_y(90.0));
+    let mut g2 = Group::new();
+    g2.set_transform(scaling(1.0, 2.0, 3.0));
+    let mut s = Sphere::new();
+    s.set_transform(translation(5.0, 0.0, 0.0));
+    g2.add_child(Box::new(s));
+    g1.add_child(Box::new(g2));
+    g1
+}
+
+#[test]
+fn world_to_object_through_groups() {
+    let mut g1 = Group::new();
+    g1.set_transform(rotation_y(90.0));
+    let mut g2 = Group::new();
+    g2.set_transform(scaling(2.0, 2.0, 2.0));
+    let mut s = Sphere::new();
+    s.set_transform(translation(5.0, 0.0, 0.0));
+    g2.add_child(Box::new(s));
+    g1.add_child(Box::new(g2));
+
+    let s = g1.children()[0].children()[0].as_ref();
+    assert_eq!(s.world_to_object(&point(-2.0, 0.0, -10.0)), point(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn normal_to_world_through_groups() {
+    let g1 = nested_group();
+    let s = g1.children()[0].children()[0].as_ref();
+    let coord = (3.0 as f32).sqrt() / 3.0;
+    let n = s.normal_to_world(&vector(coord, coord, coord));
+    assert_eq!(n, vector(0.28571, 0.42857, -0.85714));
+}
+
+#[test]
+fn normal_on_group_child() {
+    let g1 = nested_group();
+    let s = g1.children()[0].children()[0].as_ref();
+    let n = s.normal_at(&point(1.7321, 1.1547, -5.5774), &Intersection::new(0.0, s));
+    assert!((n.0 - 0.2857).abs() < 0.0001);
+    assert!((n.1 - 0.42854).abs() < 0.0001);
+    assert!((n.2 + 0.85716).abs() < 0.0001);
+}
+
+#[test]
+fn shape_ids() {
+    let a = glass_sphere();
+    let b = a.clone();
+    assert_ne!(a.id(), b.id());
+    assert_ne!(&a as &dyn Shape, &b as &dyn Shape);
+    assert_eq!(&a as &dyn Shape, &a as &dyn Shape);
+
+    // identical spheres in the same place are still tracked separately
+    let r = Ray {
+        origin: point(0.0, 0.0, -4.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let xs = vec![
+        Intersection::new(3.0, &a),
+        Intersection::new(3.0, &b),
+        Intersection::new(5.0, &a),
+        Intersection::new(5.0, &b)
+    ];
+    let comps = xs[1].prepare_computations(&r, xs.clone());
+    assert_eq!((comps.n1, comps.n2), (Some(1.5), Some(1.5)));
+    let comps = xs[2].prepare_computations(&r, xs.clone());
+    assert_eq!((comps.n1, comps.n2), (Some(1.5), Some(1.5)));
+    let comps = xs[3].prepare_computations(&r, xs.clone());
+    assert_eq!((comps.n1, comps.n2), (Some(1.5), Some(1.0)));
+}
+
+#[test]
+fn cached_inverse_transforms() {
+    let mut s = Sphere::new();
+    assert_eq!(*s.inverse(), Matrix4x4::ident());
+    let m = translation(1.0, 2.0, 3.0).scale(2.0, 0.5, 1.0);
+    s.set_transform(m);
+    assert_eq!(*s.transform(), m);
+    assert_eq!(*s.inverse(), m.invert());
+}
+
+#[test]
+fn group_transform_set_after_children() {
+    let mut g1 = Group::new();
+    let mut g2 = Group::new();
+    let mut s = Sphere::new();
+    s.set_transform(translation(5.0, 0.0, 0.0));
+    g2.add_child(Box::new(s));
+    g1.add_child(Box::new(g2));
+    let before = g1.children()[0].children()[0].world_to_object(&point(-2.0, 0.0, -10.0));
+    assert_eq!(before, point(-7.0, 0.0, -10.0));
+
+    // the new transform reaches the sphere two levels down
+    g1.set_transform(rotation_y(90.0).scale(2.0, 2.0, 2.0));
+    let s = g1.children()[0].children()[0].as_ref();
+    assert_eq!(s.world_to_object(&point(-2.0, 0.0, -10.0)), point(0.0, 0.0, -1.0));
+    let coord = (3.0 as f32).sqrt() / 3.0;
+    let expected = vector(coord, coord, coord);
+    let n = s.normal_to_world(&expected);
+    assert_eq!(n, vector(coord, coord, -coord));
+}
+
+#[test]
+fn csg_intersection_allowed() {
+    let rules = vec![
+        (CsgOperation::Union, true, true, true, false),
+        (CsgOperation::Union, true, false, false, true),
+        (CsgOperation::Union, false, true, false, false),
+        (CsgOperation::Union, false, false, false, true),
+        (CsgOperation::Intersection, true, true, true, true),
+        (CsgOperation::Intersection, true, false, false, false),
+        (CsgOperation::Intersection, false, true, false, true),
+        (CsgOperation::Intersection, false, false, true, false),
+        (CsgOperation::Difference, true, true, true, false),
+        (CsgOperation::Difference, true, false, false, true),
+        (CsgOperation::Difference, false, true, true, true),
+        (CsgOperation::Difference, false, false, true, false)
+    ];
+    for (operation, left_hit, in_left, in_right, allowed) in rules {
+        assert_eq!(operation.intersection_allowed(left_hit, in_left, in_right), allowed);
+    }
+}
+
+#[test]
+fn csg_filter_intersections() {
+    let operations = vec![
+        (CsgOperation::Union, 0, 3),
+        (CsgOperation::Intersection, 1, 2),
+        (CsgOperation::Difference, 0, 1)
+    ];
+    for (operation, x0, x1) in operations {
+        let c = Csg::new(operation, Box::new(Sphere::new()), Box::new(Cube::new()));
+        let s1 = c.left();
+        let s2 = c.right();
+        let intersections = vec![
+            Intersection::new(1.0, s1),
+            Intersection::new(2.0, s2),
+            Intersection::new(3.0, s1),
+            Intersection::new(4.0, s2)
+        ];
+        let result = c.filter_intersections(intersections.clone());
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].t, intersections[x0].t);
+        assert_eq!(result[1].t, intersections[x1].t);
+        assert_eq!(result[0].container, &c as &dyn Shape);
+    }
+}
+
+#[test]
+fn ray_misses_csg() {
+    let c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), Box::new(Cube::new()));
+    let r = Ray {
+        origin: point(0.0, 2.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    assert_eq!(c.intersect(&r).len(), 0);
+}
+
+#[test]
+fn ray_hits_csg() {
+    let mut s2 = Sphere::new();
+    s2.set_transform(translation(0.0, 0.0, 0.5));
+    let c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), Box::new(s2));
+    let r = Ray {
+        origin: point(0.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let intersections = c.intersect(&r);
+    assert_eq!(intersections.len(), 2);
+    assert_eq!(intersections[0].t, 4.0);
+    assert_eq!(intersections[0].object, c.left());
+    assert_eq!(intersections[1].t, 6.5);
+    assert_eq!(intersections[1].object, c.right());
+}
+
+#[test]
+fn csg_lens_refraction() {
+    // lens carved from two overlapping glass spheres, the ray should leave
+    // the lens into air even though it exits through the other sphere
+    let mut a = glass_sphere();
+    a.set_transform(translation(0.0, 0.0, 0.5));
+    let mut b = glass_sphere();
+    b.set_transform(translation(0.0, 0.0, -0.5));
+    let lens = Csg::new(CsgOperation::Intersection, Box::new(a), Box::new(b));
+    let r = Ray {
+        origin: point(0.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let intersections = lens.intersect(&r);
+    assert_eq!(intersections.len(), 2);
+    assert_eq!(intersections[0].t, 4.5);
+    assert_eq!(intersections[1].t, 5.5);
+
+    let comps = intersections[0].prepare_computations(&r, intersections.clone());
+    assert_eq!(comps.n1, Some(1.0));
+    assert_eq!(comps.n2, Some(1.5));
+    let comps = intersections[1].prepare_computations(&r, intersections.clone());
+    assert_eq!(comps.n1, Some(1.5));
+    assert_eq!(comps.n2, Some(1.0));
+}
+
+#[test]
+fn shape_bounds() {
+    let b = Sphere::new().bounds();
+    assert_eq!(b, BoundingBox::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0)));
+    let b = Cube::new().bounds();
+    assert_eq!(b, BoundingBox::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0)));
+
+    let b = Plane::new().bounds();
+    assert!(!b.is_finite());
+    assert_eq!(b.min.1, 0.0);
+    assert_eq!(b.max.1, 0.0);
+
+    let b = Cylinder::new(-5.0, 3.0, true).bounds();
+    assert_eq!(b, BoundingBox::new(point(-1.0, -5.0, -1.0), point(1.0, 3.0, 1.0)));
+    let b = Cylinder::new(f32::NEG_INFINITY, f32::INFINITY, false).bounds();
+    assert!(!b.is_finite());
+
+    let b = Cone::new(-5.0, 3.0, true).bounds();
+    assert_eq!(b, BoundingBox::new(point(-5.0, -5.0, -5.0), point(5.0, 3.0, 5.0)));
+
+    let b = Triangle::new(point(-3.0, 7.0, 2.0), point(6.0, 2.0, -4.0), point(2.0, -1.0, -1.0)).bounds();
+    assert_eq!(b, BoundingBox::new(point(-3.0, -1.0, -4.0), point(6.0, 7.0, 2.0)));
+}
+
+#[test]
+fn transformed_bounds() {
+    let b = BoundingBox::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0));
+    let b = b.transform(&(&rotation_x(45.0) * &rotation_y(45.0)));
+    assert_eq!(b, BoundingBox::new(point(-1.41421, -1.70711, -1.70711), point(1.41421, 1.70711, 1.70711)));
+
+    let mut s = Sphere::new();
+    s.set_transform(translation(1.0, -3.0, 5.0).scale(0.5, 2.0, 4.0));
+    assert_eq!(s.parent_space_bounds(), BoundingBox::new(point(0.5, -5.0, 1.0), point(1.5, -1.0, 9.0)));
+
+    // infinite shapes stay infinite rather than turning into NaN
+    let mut p = Plane::new();
+    p.set_transform(rotation_x(90.0));
+    assert!(!p.parent_space_bounds().is_finite());
+
+    // empty boxes stay empty
+    let b = BoundingBox::empty().transform(&translation(1.0, 2.0, 3.0));
+    assert!(b.is_empty());
+}
+
+#[test]
+fn group_and_csg_bounds() {
+    let mut s = Sphere::new();
+    s.set_transform(translation(2.0, 5.0, -3.0).scale(2.0, 2.0, 2.0));
+    let mut c = Cylinder::new(-2.0, 2.0, false);
+    c.set_transform(translation(-4.0, -1.0, 4.0).scale(0.5, 1.0, 0.5));
+    let mut g = Group::new();
+    g.add_child(Box::new(s.clone()));
+    g.add_child(Box::new(c.clone()));
+    assert_eq!(g.bounds(), BoundingBox::new(point(-4.5, -3.0, -5.0), point(4.0, 7.0, 4.5)));
+
+    let shape = Csg::new(CsgOperation::Difference, Box::new(s), Box::new(c));
+    assert_eq!(shape.bounds(), BoundingBox::new(point(-4.5, -3.0, -5.0), point(4.0, 7.0, 4.5)));
+
+    // empty groups, like an obj file's unused default group, don't
+    // make their parent unbounded
+    let mut empty = Group::new();
+    empty.set_transform(translation(1.0, 0.0, 0.0));
+    let mut g = Group::new();
+    g.add_child(Box::new(empty));
+    assert!(g.bounds().is_empty());
+    g.add_child(Box::new(Sphere::new()));
+    assert_eq!(g.bounds(), BoundingBox::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0)));
+}
+
+#[test]
+fn ray_bounding_box_intersection() {
+    let b = BoundingBox::new(point(5.0, -2.0, 0.0), point(11.0, 4.0, 7.0));
+    let rays = vec![
+        (point(15.0, 1.0, 2.0), vector(-1.0, 0.0, 0.0), true),
+        (point(-5.0, -1.0, 4.0), vector(1.0, 0.0, 0.0), true),
+        (point(7.0, 6.0, 5.0), vector(0.0, -1.0, 0.0), true),
+        (point(9.0, -5.0, 6.0), vector(0.0, 1.0, 0.0), true),
+        (point(8.0, 2.0, 12.0), vector(0.0, 0.0, -1.0), true),
+        (point(6.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), true),
+        (point(8.0, 1.0, 3.5), vector(0.0, 0.0, 1.0), true),
+        (point(9.0, -1.0, -8.0), vector(2.0, 4.0, 6.0), false),
+        (point(8.0, 3.0, -4.0), vector(6.0, 2.0, 4.0), false),
+        (point(9.0, -1.0, -2.0), vector(4.0, 6.0, 2.0), false),
+        (point(4.0, 0.0, 9.0), vector(0.0, 0.0, -1.0), false),
+        (point(8.0, 6.0, -1.0), vector(0.0, -1.0, 0.0), false),
+        (point(12.0, 5.0, 4.0), vector(-1.0, 0.0, 0.0), false),
+        // boxes behind the ray still count
+        (point(15.0, 1.0, 2.0), vector(1.0, 0.0, 0.0), true)
+    ];
+    for (origin, direction, result) in rays {
+        let r = Ray {
+            origin,
+            direction: direction.normalize()
+        };
+        assert_eq!(b.intersects(&r), result);
+    }
+}
+
+#[test]
+fn group_bvh_matches_children() {
+    let mut g = Group::new();
+    for i in 0..20 {
+        let mut s = Sphere::new();
+        s.set_transform(translation((i as f32) * 3.0 - 30.0, 0.0, 0.0));
+        g.add_child(Box::new(s));
+    }
+    let r = Ray {
+        origin: point(-9.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let brute_force: Vec<f32> = g.intersect(&r).iter().map(|i| i.t).collect();
+    g.build_bvh();
+    let intersections = g.intersect(&r);
+    assert_eq!(intersections.len(), 2);
+    assert_eq!(intersections.iter().map(|i| i.t).collect::<Vec<f32>>(), brute_force);
+    assert_eq!(intersections[0].object, g.children()[7].as_ref());
+}
+
+// primitive defined outside the crate: unit disc in the xz plane
+#[derive(Debug, Clone)]
+struct Disc {
+    data: ShapeData
+}
+
+impl Shape for Disc {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        if ray.direction.1.abs() < EPSILON {
+            return vec![]
+        }
+        let t = -ray.origin.1 / ray.direction.1;
+        let p = ray.position(t);
+        if p.0.powi(2) + p.2.powi(2) > 1.0 {
+            return vec![]
+        }
+        vec![Intersection::new(t, self)]
+    }
+
+    fn local_normal_at(&self, _point: &Vec4, _hit: &Intersection) -> Vec4 {
+        vector(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point(-1.0, 0.0, -1.0), point(1.0, 0.0, 1.0))
+    }
+}
+
+#[test]
+fn custom_shape_in_world() {
+    let mut disc = Disc {
+        data: ShapeData::new()
+    };
+    disc.set_transform(translation(0.0, -1.0, 0.0).scale(2.0, 2.0, 2.0));
+    disc.material_mut().color = color(1.0, 0.0, 0.0);
+    disc.material_mut().ambient = 1.0;
+    disc.material_mut().diffuse = 0.0;
+    disc.material_mut().specular = 0.0;
+
+    let mut w = World::new();
+    *w.shapes_mut() = vec![Box::new(disc)];
+    let hit = Ray {
+        origin: point(1.5, 1.0, 0.0),
+        direction: vector(0.0, -1.0, 0.0)
+    };
+    let miss = Ray {
+        origin: point(2.5, 1.0, 0.0),
+        direction: vector(0.0, -1.0, 0.0)
+    };
+    assert_eq!(w.color_at(&hit, 1), color(1.0, 0.0, 0.0));
+    assert_eq!(w.color_at(&miss, 1), color(0.0, 0.0, 0.0));
+
+    // works inside groups and hierarchies like the built in shapes
+    let mut g = Group::new();
+    g.add_child(w.shapes()[0].clone());
+    *w.shapes_mut() = vec![Box::new(g)];
+    w.build_bvh();
+    assert_eq!(w.color_at(&hit, 1), color(1.0, 0.0, 0.0));
+    assert_eq!(w.color_at(&miss, 1), color(0.0, 0.0, 0.0));
+}
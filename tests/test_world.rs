@@ -1,12 +1,15 @@
 use ray_tracer::implementations::{
-    camera::Camera, 
+    camera::*, 
     intersection::*, 
     light::*, 
     matrices::*, 
     ray::*, 
     tuples::*, 
     world::*,
-    shape::*
+    shape::*,
+    shapes::{cone::Cone, cube::Cube, cylinder::Cylinder, group::Group, plane::Plane, sphere::{glass_sphere, Sphere}, triangle::Triangle},
+    pattern::Pattern,
+    patterns::checker_pattern::CheckerPattern
 };
 
 #[test]
@@ -31,28 +34,21 @@ fn shade_hit() {
         origin: point(0.0, 0.0, -5.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let shape = &w.shapes[0];
-    let i = Intersection {
-        object: shape,
-        t: 4.0
-    };
+    let shape = &w.shapes()[0];
+    let i = Intersection::new(4.0, shape.as_ref());
     let comps = i.prepare_computations(&r, vec![i]);
     let c = w.shade_hit(&comps, 0);
     assert_eq!(c, color(0.38066, 0.47583, 0.2855));
 
-    w.light = Light {
-        position: point(0.0, 0.25, 0.0),
-        intensity: color(1.0, 1.0, 1.0)
-    };
+    w.lights = vec![
+        Light::new(point(0.0, 0.25, 0.0), color(1.0, 1.0, 1.0))
+    ];
     let r = Ray {
         origin: point(0.0, 0.0, 0.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let shape = &w.shapes[1];
-    let i = Intersection {
-        object: shape,
-        t: 0.5
-    };
+    let shape = &w.shapes()[1];
+    let i = Intersection::new(0.5, shape.as_ref());
     let comps = i.prepare_computations(&r, vec![i]);
     let c = w.shade_hit(&comps, 0);
     assert_eq!(c, color(0.1, 0.1, 0.1));
@@ -79,16 +75,16 @@ fn color_at() {
 
     // ray in-between two concentric spheres and pointed at inner
     // color should be color of inner sphere
-    let mut outer = &mut w.shapes[0];
-    outer.material.ambient = 1.0;
-    let mut inner = &mut w.shapes[1];
-    inner.material.ambient = 1.0;
+    let mut outer = &mut w.shapes_mut()[0];
+    outer.material_mut().ambient = 1.0;
+    let mut inner = &mut w.shapes_mut()[1];
+    inner.material_mut().ambient = 1.0;
     let r = Ray {
         origin: point(0.0, 0.0, 0.75),
         direction: vector(0.0, 0.0, -1.0)
     };
     let c = w.color_at(&r, 1);
-    assert_eq!(c, w.shapes[1].material.color);
+    assert_eq!(c, w.shapes()[1].material().color);
 }
 
 #[test]
@@ -131,8 +127,8 @@ fn ray_for_pixel() {
     assert_eq!(r.origin, point(0.0, 0.0, 0.0));
     assert_eq!(r.direction, vector(0.66519, 0.33259, -0.66851));
 
-    c.transform = rotation_y(45.0)
-        .translate(0.0, -2.0, 5.0);
+    c.set_transform(rotation_y(45.0)
+        .translate(0.0, -2.0, 5.0));
     let r = c.ray_for_pixel(100, 50);
     assert_eq!(r.origin, point(0.0, 2.0, -5.0));
     assert_eq!(r.direction, vector((2.0 as f32).sqrt() / 2.0, 0.0, -(2.0 as f32).sqrt() / 2.0));
@@ -145,53 +141,652 @@ fn render_test() {
     let from = point(0.0, 0.0, -5.0);
     let to = point(0.0, 0.0, 0.0);
     let up = vector(0.0, 1.0, 0.0);
-    c.transform = view_transform(&from, &to, &up);
+    c.set_transform(view_transform(&from, &to, &up));
     let image = c.render(&w, 1);
     assert_eq!(image.get(5, 5), &color(0.38066, 0.47583, 0.2855));
 }
 
 #[test]
-fn is_shadowed() {
+fn shadow_attenuation() {
     let w = World::new();
     let p = point(0.0, 10.0, 0.0);
-    assert_eq!(w.is_shadowed(&p), false);
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(1.0, 1.0, 1.0));
 
     let p = point(10.0, -10.0, 10.0);
-    assert_eq!(w.is_shadowed(&p), true);
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(0.0, 0.0, 0.0));
 
     let p = point(-20.0, 20.0, -20.0);
-    assert_eq!(w.is_shadowed(&p), false);
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(1.0, 1.0, 1.0));
 
     let p = point(-2.0, 2.0, -2.0);
-    assert_eq!(w.is_shadowed(&p), false);
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(1.0, 1.0, 1.0));
 }
 
 #[test]
 fn shade_hit_with_shadow() {
     let mut w = World::new();
-    w.light = Light {
-        position: point(0.0, 0.0, -10.0),
-        intensity: color(1.0, 1.0, 1.0)
-    };
+    w.lights = vec![
+        Light::new(point(0.0, 0.0, -10.0), color(1.0, 1.0, 1.0))
+    ];
 
-    let s1 = Shape::new(ShapeType::Sphere);
-    let mut s2 = Shape::new(ShapeType::Sphere);
-    s2.transform = translation(0.0, 0.0, 10.0);
+    let s1 = Sphere::new();
+    let mut s2 = Sphere::new();
+    s2.set_transform(translation(0.0, 0.0, 10.0));
 
     let r = Ray {
         origin: point(0.0, 0.0, 5.0),
         direction: vector(0.0, 0.0, 1.0)
     };
-    let i = Intersection {
-        object: &s2.clone(),
-        t: 4.0
-    };
+    let s2_clone = s2.clone();
+    let i = Intersection::new(4.0, &s2_clone);
 
-    w.shapes = vec![
-        s1,
-        s2
+    *w.shapes_mut() = vec![
+        Box::new(s1),
+        Box::new(s2)
     ];
     let comps = i.prepare_computations(&r, vec![i]);
     let c = w.shade_hit(&comps, 1);
     assert_eq!(c, color(0.1, 0.1, 0.1));
-}
\ No newline at end of file
+}
+#[test]
+fn shade_hit_with_multiple_lights() {
+    let mut w = World::new();
+    let r = Ray {
+        origin: point(0.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let single = {
+        let i = Intersection::new(4.0, w.shapes()[0].as_ref());
+        let comps = i.prepare_computations(&r, vec![i]);
+        w.shade_hit(&comps, 0)
+    };
+
+    // the same light twice doubles the contribution
+    w.lights.push(Light::new(point(-10.0, 10.0, -10.0), color(1.0, 1.0, 1.0)));
+    let i = Intersection::new(4.0, w.shapes()[0].as_ref());
+    let comps = i.prepare_computations(&r, vec![i]);
+    assert_eq!(w.shade_hit(&comps, 0), &single * 2.0);
+
+    // a light blocked by the outer sphere only adds its ambient term
+    w.lights[1] = Light::new(point(0.0, 0.0, 10.0), color(1.0, 1.0, 1.0));
+    let i = Intersection::new(4.0, w.shapes()[0].as_ref());
+    let comps = i.prepare_computations(&r, vec![i]);
+    assert_eq!(w.shadow_attenuation(&w.lights[1], &comps.over_point), color(0.0, 0.0, 0.0));
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &comps.over_point), color(1.0, 1.0, 1.0));
+    let ambient = &color(0.8, 1.0, 0.6) * 0.1;
+    assert_eq!(w.shade_hit(&comps, 0), &single + &ambient);
+}
+
+#[test]
+fn area_light() {
+    let light = Light::area(
+        point(0.0, 0.0, 0.0),
+        vector(2.0, 0.0, 0.0), 4,
+        vector(0.0, 0.0, 1.0), 2,
+        false,
+        color(1.0, 1.0, 1.0)
+    );
+    assert_eq!(light.position, point(1.0, 0.0, 0.5));
+    match light.light_type {
+        LightType::Area { uvec, vvec, .. } => {
+            assert_eq!(uvec, vector(0.5, 0.0, 0.0));
+            assert_eq!(vvec, vector(0.0, 0.0, 0.5));
+        },
+        _ => panic!("expected an area light")
+    }
+    assert_eq!(light.samples(&point(0.0, 0.0, 0.0)).len(), 8);
+
+    assert_eq!(light.point_on_light(0, 0, None), point(0.25, 0.0, 0.25));
+    assert_eq!(light.point_on_light(1, 0, None), point(0.75, 0.0, 0.25));
+    assert_eq!(light.point_on_light(0, 1, None), point(0.25, 0.0, 0.75));
+    assert_eq!(light.point_on_light(2, 0, None), point(1.25, 0.0, 0.25));
+    assert_eq!(light.point_on_light(3, 1, None), point(1.75, 0.0, 0.75));
+}
+
+#[test]
+fn jittered_area_light_samples() {
+    let light = Light::area(
+        point(0.0, 0.0, 0.0),
+        vector(2.0, 0.0, 0.0), 4,
+        vector(0.0, 0.0, 1.0), 2,
+        true,
+        color(1.0, 1.0, 1.0)
+    );
+    let p = point(0.3, 2.0, -1.0);
+    let samples = light.samples(&p);
+    // same point, same samples
+    assert_eq!(samples, light.samples(&p));
+    for (index, sample) in samples.iter().enumerate() {
+        let (u, v) = ((index % 4) as f32, (index / 4) as f32);
+        assert!(sample.0 >= u * 0.5 && sample.0 <= (u + 1.0) * 0.5);
+        assert!(sample.2 >= v * 0.5 && sample.2 <= (v + 1.0) * 0.5);
+        assert_eq!(sample.1, 0.0);
+    }
+    assert_ne!(samples[0], light.point_on_light(0, 0, None));
+}
+
+#[test]
+fn area_light_intensity() {
+    let w = World::new();
+    let light = Light::area(
+        point(-0.5, -0.5, -5.0),
+        vector(1.0, 0.0, 0.0), 2,
+        vector(0.0, 1.0, 0.0), 2,
+        false,
+        color(1.0, 1.0, 1.0)
+    );
+    assert_eq!(w.intensity_at(&light, &point(0.0, 0.0, 2.0)), color(0.0, 0.0, 0.0));
+    assert_eq!(w.intensity_at(&light, &point(1.0, -1.0, 2.0)), color(0.25, 0.25, 0.25));
+    assert_eq!(w.intensity_at(&light, &point(1.5, 0.0, 2.0)), color(0.5, 0.5, 0.5));
+    assert_eq!(w.intensity_at(&light, &point(1.25, 1.25, 3.0)), color(0.75, 0.75, 0.75));
+    assert_eq!(w.intensity_at(&light, &point(0.0, 0.0, -2.0)), color(1.0, 1.0, 1.0));
+
+    // point lights are all or nothing
+    let light = &w.lights[0];
+    assert_eq!(w.intensity_at(light, &point(0.0, 10.0, 0.0)), color(1.0, 1.0, 1.0));
+    assert_eq!(w.intensity_at(light, &point(10.0, -10.0, 10.0)), color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn lighting_with_area_light() {
+    let light = Light::area(
+        point(-0.5, -0.5, -5.0),
+        vector(1.0, 0.0, 0.0), 2,
+        vector(0.0, 1.0, 0.0), 2,
+        false,
+        color(1.0, 1.0, 1.0)
+    );
+    let mut shape = Sphere::new();
+    shape.material_mut().ambient = 0.1;
+    shape.material_mut().diffuse = 0.9;
+    shape.material_mut().specular = 0.0;
+    shape.material_mut().color = color(1.0, 1.0, 1.0);
+    let eye = point(0.0, 0.0, -5.0);
+
+    let p = point(0.0, 0.0, -1.0);
+    let eyev = (&eye - &p).normalize();
+    let normalv = vector(p.0, p.1, p.2);
+    let result = lighting(shape.material(), &shape, &light, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    assert_eq!(result, color(0.9965, 0.9965, 0.9965));
+
+    let p = point(0.0, 0.7071, -0.7071);
+    let eyev = (&eye - &p).normalize();
+    let normalv = vector(p.0, p.1, p.2);
+    let result = lighting(shape.material(), &shape, &light, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    assert!((result.0 - 0.62318).abs() < 1e-4);
+}
+
+#[test]
+fn spotlight() {
+    let light = Light::spot(
+        point(0.0, 10.0, 0.0),
+        vector(0.0, -2.0, 0.0),
+        20.0,
+        30.0,
+        color(1.0, 1.0, 1.0)
+    );
+    // on the axis, inside the inner cone, in the falloff and outside
+    assert_eq!(light.spot_factor(&point(0.0, 0.0, 0.0)), 1.0);
+    assert_eq!(light.spot_factor(&point(3.0, 0.0, 0.0)), 1.0);
+    let falloff = light.spot_factor(&point(5.0, 0.0, 0.0));
+    assert!(falloff > 0.0 && falloff < 1.0);
+    assert!(light.spot_factor(&point(4.5, 0.0, 0.0)) > falloff);
+    assert_eq!(light.spot_factor(&point(6.0, 0.0, 0.0)), 0.0);
+    assert_eq!(light.spot_factor(&point(0.0, 20.0, 0.0)), 0.0);
+
+    let shape = Plane::new();
+    let eyev = vector(0.0, 1.0, 0.0);
+    let normalv = vector(0.0, 1.0, 0.0);
+    let lit = lighting(shape.material(), &shape, &light, &point(0.0, 0.0, 0.0), &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    let unlit = lighting(shape.material(), &shape, &light, &point(6.0, 0.0, 0.0), &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    let faded = lighting(shape.material(), &shape, &light, &point(5.0, 0.0, 0.0), &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    assert_eq!(unlit, color(0.1, 0.1, 0.1));
+    assert!(faded.0 > unlit.0 && faded.0 < lit.0);
+
+    // shadows are cast from the spotlight's position
+    let mut w = World::new();
+    w.lights = vec![Light::spot(
+        point(0.0, 0.0, -10.0),
+        vector(0.0, 0.0, 1.0),
+        10.0,
+        15.0,
+        color(1.0, 1.0, 1.0)
+    )];
+    assert_eq!(w.intensity_at(&w.lights[0], &point(0.0, 0.0, 10.0)), color(0.0, 0.0, 0.0));
+    assert_eq!(w.intensity_at(&w.lights[0], &point(0.0, 0.0, -5.0)), color(1.0, 1.0, 1.0));
+}
+
+#[test]
+#[should_panic(expected = "Spotlight inner angle 30 is wider than its outer angle 20")]
+fn spotlight_with_swapped_angles() {
+    Light::spot(point(0.0, 10.0, 0.0), vector(0.0, -1.0, 0.0), 30.0, 20.0, color(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn directional_light() {
+    let light = Light::directional(vector(0.0, -2.0, 0.0), color(1.0, 1.0, 1.0));
+    let (direction, distance) = light.direction_from(&light.position, &point(5.0, 3.0, -7.0));
+    assert_eq!(direction, vector(0.0, 1.0, 0.0));
+    assert_eq!(distance, f32::INFINITY);
+
+    // every point sees the light from the same angle
+    let shape = Plane::new();
+    let eyev = vector(0.0, 1.0, 0.0);
+    let normalv = vector(0.0, 1.0, 0.0);
+    let near = lighting(shape.material(), &shape, &light, &point(0.0, 0.0, 0.0), &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    let far = lighting(shape.material(), &shape, &light, &point(1000.0, 0.0, -1000.0), &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    assert_eq!(near, color(1.9, 1.9, 1.9));
+    assert_eq!(near, far);
+
+    // shadow rays don't stop at the light's position
+    let mut w = World::new();
+    w.lights = vec![light];
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &point(0.0, -5.0, 0.0)), color(0.0, 0.0, 0.0));
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &point(0.0, -1000.0, 0.0)), color(0.0, 0.0, 0.0));
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &point(0.0, 5.0, 0.0)), color(1.0, 1.0, 1.0));
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &point(5.0, -5.0, 0.0)), color(1.0, 1.0, 1.0));
+    assert_eq!(w.intensity_at(&w.lights[0], &point(0.0, -5.0, 0.0)), color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn light_attenuation() {
+    assert_eq!(Attenuation::None.factor(10.0), 1.0);
+    assert_eq!(Attenuation::InverseSquare.factor(2.0), 0.25);
+    let polynomial = Attenuation::polynomial(1.0, 0.5, 0.25);
+    assert_eq!(polynomial, Attenuation::Polynomial { constant: 1.0, linear: 0.5, quadratic: 0.25 });
+    assert_eq!(polynomial.factor(0.0), 1.0);
+    assert_eq!(polynomial.factor(2.0), 1.0 / 3.0);
+
+    // inverse square stops brightening closer than one unit
+    assert_eq!(Attenuation::InverseSquare.factor(1.0), 1.0);
+    assert_eq!(Attenuation::InverseSquare.factor(0.5), 1.0);
+    assert_eq!(Attenuation::InverseSquare.factor(0.0), 1.0);
+
+    let shape = Plane::new();
+    let eyev = vector(0.0, 1.0, 0.0);
+    let normalv = vector(0.0, 1.0, 0.0);
+    let p = point(0.0, 0.0, 0.0);
+    let mut light = Light::new(point(0.0, 2.0, 0.0), color(1.0, 1.0, 1.0));
+    assert_eq!(lighting(shape.material(), &shape, &light, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0)), color(1.9, 1.9, 1.9));
+
+    // ambient is untouched, diffuse and specular fall off together
+    light.attenuation = Attenuation::InverseSquare;
+    let result = lighting(shape.material(), &shape, &light, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0));
+    assert_eq!(result, color(0.1 + 1.8 * 0.25, 0.1 + 1.8 * 0.25, 0.1 + 1.8 * 0.25));
+
+    // never brighter than the unattenuated light
+    light.position = point(0.0, 0.5, 0.0);
+    assert_eq!(lighting(shape.material(), &shape, &light, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0)), color(1.9, 1.9, 1.9));
+
+    // directional lights ignore attenuation
+    let mut sun = Light::directional(vector(0.0, -1.0, 0.0), color(1.0, 1.0, 1.0));
+    assert_eq!(sun.falloff(f32::INFINITY), 1.0);
+    sun.attenuation = Attenuation::InverseSquare;
+    assert_eq!(sun.falloff(f32::INFINITY), 1.0);
+    assert_eq!(lighting(shape.material(), &shape, &sun, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0)), color(1.9, 1.9, 1.9));
+    sun.attenuation = Attenuation::polynomial(1.0, 1.0, 1.0);
+    assert_eq!(lighting(shape.material(), &shape, &sun, &p, &eyev, &normalv, &color(1.0, 1.0, 1.0)), color(1.9, 1.9, 1.9));
+}
+
+#[test]
+#[should_panic(expected = "Polynomial attenuation needs a positive constant")]
+fn polynomial_attenuation_without_constant() {
+    Attenuation::polynomial(0.0, 0.0, 0.0);
+}
+
+#[test]
+fn transparent_colored_shadows() {
+    let mut w = World::new();
+    let p = point(0.0, 0.0, 5.0);
+    w.lights = vec![Light::new(point(0.0, 0.0, -10.0), color(1.0, 1.0, 1.0))];
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(0.0, 0.0, 0.0));
+
+    // each glass sphere filters the light once, not once per surface
+    let mut glass = glass_sphere();
+    glass.material_mut().transparency = 0.5;
+    glass.material_mut().color = color(1.0, 0.5, 0.0);
+    let mut inner = glass.clone();
+    inner.set_transform(scaling(0.5, 0.5, 0.5));
+    *w.shapes_mut() = vec![Box::new(glass), Box::new(inner)];
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(0.25, 0.0625, 0.0));
+
+    // shapes behind the point don't count, the sphere the point is
+    // inside still does
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &point(0.0, 0.0, -0.75)), color(0.5, 0.25, 0.0));
+
+    // shapes that don't cast shadows are ignored
+    w.shapes_mut()[0].set_visibility(Visibility { casts_shadow: false, ..Visibility::new() });
+    w.shapes_mut()[1].set_visibility(Visibility { casts_shadow: false, ..Visibility::new() });
+    w.shapes_mut()[1].material_mut().transparency = 0.0;
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(1.0, 1.0, 1.0));
+    w.shapes_mut()[1].set_visibility(Visibility::new());
+    assert_eq!(w.shadow_attenuation(&w.lights[0], &p), color(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn visibility_flags() {
+    let mut w = World::new();
+    let r = Ray {
+        origin: point(0.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let visible = w.color_at(&r, 1);
+    assert_eq!(visible, color(0.38066, 0.47583, 0.2855));
+
+    // camera rays see through to the inner sphere, which the hidden outer
+    // one still shadows
+    w.shapes_mut()[0].set_visibility(Visibility { visible_to_camera: false, ..Visibility::new() });
+    assert_eq!(w.color_at(&r, 1), color(0.1, 0.1, 0.1));
+    w.shapes_mut()[0].set_visibility(Visibility { visible_to_camera: false, casts_shadow: false, ..Visibility::new() });
+    let mut inner_only = World::new();
+    inner_only.shapes_mut().remove(0);
+    assert_eq!(w.color_at(&r, 1), inner_only.color_at(&r, 1));
+    w.shapes_mut()[0].set_visibility(Visibility::new());
+
+    // a reflective floor stops reflecting the spheres
+    let mut floor = Plane::new();
+    floor.material_mut().reflective = 0.5;
+    floor.set_transform(translation(0.0, -1.0, 0.0));
+    w.add_shape(Box::new(floor));
+    let r = Ray {
+        origin: point(0.0, 0.0, -3.0),
+        direction: vector(0.0, -(2.0 as f32).sqrt() / 2.0, (2.0 as f32).sqrt() / 2.0)
+    };
+    let i = Intersection::new((2.0 as f32).sqrt(), w.shapes()[2].as_ref());
+    let comps = i.prepare_computations(&r, vec![i]);
+    assert_ne!(w.reflected_color(&comps, 1), color(0.0, 0.0, 0.0));
+    w.shapes_mut()[0].set_visibility(Visibility { visible_in_reflections: false, ..Visibility::new() });
+    w.shapes_mut()[1].set_visibility(Visibility { visible_in_reflections: false, ..Visibility::new() });
+    let i = Intersection::new((2.0 as f32).sqrt(), w.shapes()[2].as_ref());
+    let comps = i.prepare_computations(&r, vec![i]);
+    assert_eq!(w.reflected_color(&comps, 1), color(0.0, 0.0, 0.0));
+    let r = Ray {
+        origin: point(0.0, 0.0, -5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    assert_eq!(w.color_at(&r, 1), visible);
+
+    // flags on a group apply to everything in it, whether they are set
+    // before or after the children are added
+    let mut w = World::new();
+    let mut fixtures = Group::new();
+    for shape in w.shapes() {
+        fixtures.add_child(shape.clone());
+    }
+    let mut nested = Group::new();
+    nested.set_visibility(Visibility { visible_to_camera: false, ..Visibility::new() });
+    nested.add_child(Box::new(Sphere::new()));
+    assert!(!nested.children()[0].world_visibility().visible_to_camera);
+    assert!(nested.children()[0].visibility().visible_to_camera);
+    *w.shapes_mut() = vec![Box::new(fixtures)];
+    assert_eq!(w.color_at(&r, 1), visible);
+    w.shapes_mut()[0].set_visibility(Visibility { visible_to_camera: false, casts_shadow: false, ..Visibility::new() });
+    assert_eq!(w.color_at(&r, 1), color(0.0, 0.0, 0.0));
+    let hidden = &w.shapes()[0].children()[1];
+    assert!(!hidden.world_visibility().visible_to_camera && !hidden.world_visibility().casts_shadow);
+    w.shapes_mut()[0].set_visibility(Visibility::new());
+    assert_eq!(w.color_at(&r, 1), visible);
+}
+
+#[test]
+fn shape_not_receiving_shadows() {
+    let mut w = World::new();
+    w.lights = vec![Light::new(point(0.0, 0.0, -10.0), color(1.0, 1.0, 1.0))];
+    let s1 = Sphere::new();
+    let mut s2 = Sphere::new();
+    s2.set_transform(translation(0.0, 0.0, 10.0));
+    s2.set_visibility(Visibility { receives_shadow: false, ..Visibility::new() });
+    *w.shapes_mut() = vec![Box::new(s1), Box::new(s2)];
+
+    let r = Ray {
+        origin: point(0.0, 0.0, 5.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    let i = Intersection::new(4.0, w.shapes()[1].as_ref());
+    let comps = i.prepare_computations(&r, vec![i]);
+    assert_eq!(w.shade_hit(&comps, 1), color(1.9, 1.9, 1.9));
+}
+
+// small deterministic generator so the scene is the same every run
+fn next_random(seed: &mut u32) -> f32 {
+    *seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+    (*seed >> 8) as f32 / (1 << 24) as f32
+}
+
+// intersections found without any hierarchy, descending into groups
+fn intersect_without_bvh<'a>(shape: &'a dyn Shape, ray: &Ray) -> Vec<Intersection<'a>> {
+    if shape.children().is_empty() {
+        return shape.intersect(ray)
+    }
+    let local_ray = ray.transform(shape.inverse());
+    shape.children().iter()
+        .flat_map(|child| intersect_without_bvh(child.as_ref(), &local_ray))
+        .collect()
+}
+
+#[test]
+fn bvh_matches_brute_force() {
+    let mut w = World::new();
+    let mut seed = 7;
+    w.add_shape(Box::new(Plane::new()));
+    for i in 0..100 {
+        let mut s: Box<dyn Shape> = match i % 4 {
+            0 => Box::new(Sphere::new()),
+            1 => Box::new(Cube::new()),
+            2 => Box::new(Cylinder::new(-1.0, 1.0, true)),
+            _ => Box::new(Cone::new(-1.0, 0.0, true))
+        };
+        s.set_transform(translation(
+            next_random(&mut seed) * 40.0 - 20.0,
+            next_random(&mut seed) * 40.0 - 20.0,
+            next_random(&mut seed) * 40.0 - 20.0
+        ).rotate_y(next_random(&mut seed) * 360.0)
+            .scale(0.2 + next_random(&mut seed), 0.2 + next_random(&mut seed), 0.2 + next_random(&mut seed)));
+        w.add_shape(s);
+    }
+    let mut mesh = Group::new();
+    mesh.set_transform(translation(0.0, 5.0, 0.0));
+    for _ in 0..20 {
+        let mut corner = || point(
+            next_random(&mut seed) * 10.0 - 5.0,
+            next_random(&mut seed) * 10.0 - 5.0,
+            next_random(&mut seed) * 10.0 - 5.0
+        );
+        let (p1, p2, p3) = (corner(), corner(), corner());
+        mesh.add_child(Box::new(Triangle::new(p1, p2, p3)));
+    }
+    w.add_shape(Box::new(mesh));
+    let mesh_index = w.shapes().len() - 1;
+
+    let mut rays: Vec<Ray> = Vec::new();
+    for _ in 0..200 {
+        rays.push(Ray {
+            origin: point(
+                next_random(&mut seed) * 60.0 - 30.0,
+                next_random(&mut seed) * 60.0 - 30.0,
+                -40.0
+            ),
+            direction: vector(
+                next_random(&mut seed) - 0.5,
+                next_random(&mut seed) - 0.5,
+                1.0
+            ).normalize()
+        });
+    }
+
+    // compare distances and which shape was hit, by address
+    let key = |i: &Intersection| (i.t, i.object as *const dyn Shape as *const () as usize, i.u, i.v);
+    let results = |w: &World| -> Vec<Vec<(f32, usize, f32, f32)>> {
+        rays.iter()
+            .map(|r| w.intersect(r).iter().map(key).collect())
+            .collect()
+    };
+    // every shape tested directly, without going through the world
+    let brute_force = |w: &World| -> Vec<Vec<(f32, usize, f32, f32)>> {
+        rays.iter()
+            .map(|r| {
+                let mut xs: Vec<Intersection> = w.shapes().iter().flat_map(|s| intersect_without_bvh(s.as_ref(), r)).collect();
+                sort_intersections(&mut xs);
+                xs.iter().map(key).collect()
+            })
+            .collect()
+    };
+    let expected = brute_force(&w);
+    assert_eq!(results(&w), expected);
+    assert!(expected.iter().filter(|xs| xs.len() > 1).count() > 10);
+    w.build_bvh();
+    assert!(w.has_bvh());
+    assert_eq!(results(&w), expected);
+
+    // a stale hierarchy is dropped rather than missing shapes
+    let mut s = Sphere::new();
+    s.set_transform(translation(0.0, 0.0, -35.0));
+    w.add_shape(Box::new(s));
+    assert!(!w.has_bvh());
+    let r = Ray {
+        origin: point(0.0, 0.0, -40.0),
+        direction: vector(0.0, 0.0, 1.0)
+    };
+    assert!(fequals(w.intersect(&r)[0].t, 4.0));
+
+    // same for shapes moved after the hierarchy was built
+    w.build_bvh();
+    for (i, shape) in w.shapes_mut().iter_mut().enumerate().skip(1) {
+        let offset = if i % 2 == 0 { 3.0 } else { -3.0 };
+        let transform = &translation(offset, 0.0, 0.0) * shape.transform();
+        shape.set_transform(transform);
+    }
+    let moved = brute_force(&w);
+    assert_ne!(moved, expected);
+    assert_eq!(results(&w), moved);
+    w.build_bvh();
+    assert_eq!(results(&w), moved);
+
+    // and for shapes moved inside a group, whose own hierarchy would
+    // otherwise still cull rays by the shape's old bounds
+    let triangle = w.shapes()[mesh_index].children()[0].as_any().downcast_ref::<Triangle>().unwrap().clone();
+    w.shapes_mut()[mesh_index].children_mut()[0].set_transform(translation(0.0, 50.0, 0.0));
+    let centroid = &(w.shapes()[mesh_index].transform() * &translation(0.0, 50.0, 0.0)) * &point(
+        (triangle.p1.0 + triangle.p2.0 + triangle.p3.0) / 3.0,
+        (triangle.p1.1 + triangle.p2.1 + triangle.p3.1) / 3.0,
+        (triangle.p1.2 + triangle.p2.2 + triangle.p3.2) / 3.0
+    );
+    let r = Ray {
+        origin: &centroid - &(&triangle.normal * 10.0),
+        direction: triangle.normal
+    };
+    let hits_moved = |xs: Vec<Intersection>| xs.iter().any(|i| fequals(i.t, 10.0));
+    let brute_force_hits = w.shapes().iter().flat_map(|s| intersect_without_bvh(s.as_ref(), &r)).count();
+    assert!(hits_moved(w.intersect(&r)));
+    assert_eq!(w.intersect(&r).len(), brute_force_hits);
+    assert_eq!(results(&w), brute_force(&w));
+    w.build_bvh();
+    assert!(hits_moved(w.intersect(&r)));
+
+    // a group's hierarchy is dropped on its own too
+    let mut mesh = w.shapes()[mesh_index].clone();
+    mesh.children_mut()[0].set_transform(translation(0.0, 100.0, 0.0));
+    let r = r.transform(&translation(0.0, 50.0, 0.0));
+    assert!(hits_moved(mesh.intersect(&r)));
+    assert_eq!(mesh.intersect(&r).len(), intersect_without_bvh(mesh.as_ref(), &r).len());
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn world_is_shareable_between_threads() {
+    assert_send_sync::<World>();
+    assert_send_sync::<Box<dyn Shape>>();
+}
+
+#[test]
+fn threaded_render_matches_serial() {
+    let mut w = World::new();
+    w.shapes_mut()[0].material_mut().reflective = 0.5;
+    let mut checkers = CheckerPattern::new(color(1.0, 0.0, 0.0), color(0.0, 0.0, 1.0));
+    checkers.set_transform(scaling(0.25, 0.25, 0.25));
+    w.shapes_mut()[1].material_mut().set_pattern(Box::new(checkers));
+    let mut floor = Plane::new();
+    floor.set_transform(translation(0.0, -1.0, 0.0));
+    w.add_shape(Box::new(floor));
+
+    let mut c = Camera::new(24, 16, 90.0);
+    c.set_transform(view_transform(&point(0.0, 1.0, -5.0), &point(0.0, 0.0, 0.0), &vector(0.0, 1.0, 0.0)));
+    c.threads = 1;
+    let serial = c.render(&w, 3);
+    c.threads = 4;
+    let threaded = c.render(&w, 3);
+    for y in 0..16 {
+        for x in 0..24 {
+            let (a, b) = (serial.get(x, y), threaded.get(x, y));
+            assert_eq!(
+                (a.0.to_bits(), a.1.to_bits(), a.2.to_bits()),
+                (b.0.to_bits(), b.1.to_bits(), b.2.to_bits())
+            );
+        }
+    }
+}
+
+#[test]
+fn ray_for_pixel_offset() {
+    let c = Camera::new(201, 101, 90.0);
+    let (a, b) = (c.ray_for_pixel_offset(100, 50, 0.5, 0.5), c.ray_for_pixel(100, 50));
+    assert_eq!((a.origin, a.direction), (b.origin, b.direction));
+    let r = c.ray_for_pixel_offset(0, 0, 0.0, 0.0);
+    let corner = point(c.half_width, c.half_height, -1.0);
+    assert_eq!(r.direction, (&corner - &point(0.0, 0.0, 0.0)).normalize());
+}
+
+#[test]
+fn supersampling_sample_counts() {
+    let w = World::new();
+    let mut c = Camera::new(11, 11, 90.0);
+    c.set_transform(view_transform(&point(0.0, 0.0, -5.0), &point(0.0, 0.0, 0.0), &vector(0.0, 1.0, 0.0)));
+    let (single, count) = c.render_with_sample_count(&w, 3);
+    assert_eq!(count, 10 * 10);
+
+    c.sampling = Sampling::Grid(3);
+    let (_, count) = c.render_with_sample_count(&w, 3);
+    assert_eq!(count, 10 * 10 * 9);
+
+    // flat regions stay at 4 samples, only pixels across an edge refine
+    c.sampling = Sampling::Adaptive { grid: 4, threshold: 0.1 };
+    let (adaptive, count) = c.render_with_sample_count(&w, 3);
+    assert!(count > 10 * 10 * 4 && count < 10 * 10 * 20);
+    assert_eq!(adaptive.get(0, 0), single.get(0, 0));
+    let (corner, samples) = c.color_for_pixel(&w, 3, 0, 0);
+    assert_eq!(samples, 4);
+    assert_eq!(&corner, adaptive.get(0, 0));
+    // the 2 x 2 probes are traced but not averaged into refined pixels
+    let (refined, samples) = c.color_for_pixel(&w, 3, 5, 5);
+    assert_eq!(samples, 20);
+    c.sampling = Sampling::Grid(4);
+    let (grid, samples) = c.color_for_pixel(&w, 3, 5, 5);
+    assert_eq!(samples, 16);
+    assert_eq!(
+        (refined.0.to_bits(), refined.1.to_bits(), refined.2.to_bits()),
+        (grid.0.to_bits(), grid.1.to_bits(), grid.2.to_bits())
+    );
+}
+
+#[test]
+fn jittered_render_is_repeatable() {
+    let w = World::new();
+    let mut c = Camera::new(16, 12, 90.0);
+    c.set_transform(view_transform(&point(0.0, 0.0, -5.0), &point(0.0, 0.0, 0.0), &vector(0.0, 1.0, 0.0)));
+    c.sampling = Sampling::Jittered(2);
+    c.threads = 1;
+    let (serial, count) = c.render_with_sample_count(&w, 3);
+    assert_eq!(count, 15 * 11 * 4);
+    c.threads = 3;
+    let (threaded, _) = c.render_with_sample_count(&w, 3);
+    for y in 0..11 {
+        for x in 0..15 {
+            let (a, b) = (serial.get(x, y), threaded.get(x, y));
+            assert_eq!(
+                (a.0.to_bits(), a.1.to_bits(), a.2.to_bits()),
+                (b.0.to_bits(), b.1.to_bits(), b.2.to_bits())
+            );
+        }
+    }
+}